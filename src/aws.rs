@@ -0,0 +1,190 @@
+use crate::Result;
+use crate::digest::sha256_hex;
+use crate::domain::CloudProvider;
+use crate::error::AppError;
+use crate::sigv4::{Credentials, SigV4};
+use chrono::Utc;
+use reqwest::{Client, Method, Url};
+use tracing::debug;
+
+/// CloudFront 只能使用 us-east-1 区域的 ACM 证书，Route 53 和 CloudFront 的全局接口也在该区域签名
+pub const GLOBAL_REGION: &str = "us-east-1";
+
+/// 调用 AWS 接口，`secret_id` 为 Access Key ID，`secret_key` 为 Secret Access Key
+pub struct AwsClient {
+    client: Client,
+    credentials: Credentials,
+}
+
+pub struct AwsResponse {
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+impl AwsClient {
+    pub fn new(provider: &CloudProvider) -> Self {
+        AwsClient {
+            client: Client::new(),
+            credentials: Credentials {
+                access_key_id: provider.secret_id.clone(),
+                secret_access_key: provider.secret_key.clone(),
+            },
+        }
+    }
+
+    pub async fn request(
+        &self,
+        service: &str,
+        method: Method,
+        url: Url,
+        headers: &[(&str, &str)],
+        body: String,
+    ) -> Result<AwsResponse> {
+        let payload_hash = sha256_hex(body.as_bytes())?;
+        let mut signed_headers = headers.to_vec();
+        signed_headers.push(("x-amz-content-sha256", &payload_hash));
+        let signer = SigV4::new(self.credentials.clone(), GLOBAL_REGION, service);
+        let auth_headers = signer.sign(
+            method.as_str(),
+            &url,
+            &signed_headers,
+            body.as_bytes(),
+            Utc::now(),
+        )?;
+
+        debug!("AWS request: {} {}", method, url);
+        let mut request = self.client.request(method.clone(), url.clone()).body(body);
+        for (name, value) in signed_headers {
+            request = request.header(name, value);
+        }
+        for (name, value) in auth_headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let etag = response
+            .headers()
+            .get("etag")
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string);
+        let body = response.text().await?;
+        if !status.is_success() {
+            // XML 接口的错误在 <Message> 中，JSON 接口在 message 字段中
+            let message = xml_text(&body, "Message")
+                .or_else(|| {
+                    serde_json::from_str::<serde_json::Value>(&body)
+                        .ok()
+                        .and_then(|value| value["message"].as_str().map(str::to_string))
+                })
+                .unwrap_or(body);
+            return Err(AppError::CloudError(format!(
+                "aws {} {} failed: HTTP {} {}",
+                service, url, status, message
+            )));
+        }
+        Ok(AwsResponse { etag, body })
+    }
+}
+
+/// 返回全部 `<tag>` 元素的内容，不支持同名元素嵌套
+pub fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // 排除前缀相同的其他元素，如 <Items> 与 <ItemsCount>
+        if !after.starts_with(['>', ' ', '/']) {
+            rest = after;
+            continue;
+        }
+        let Some(tag_end) = after.find('>') else {
+            break;
+        };
+        if after[..tag_end].ends_with('/') {
+            elements.push(&after[tag_end + 1..tag_end + 1]);
+            rest = &after[tag_end + 1..];
+            continue;
+        }
+        let content = &after[tag_end + 1..];
+        let Some(end) = content.find(&close) else {
+            break;
+        };
+        elements.push(&content[..end]);
+        rest = &content[end + close.len()..];
+    }
+    elements
+}
+
+pub fn xml_texts(xml: &str, tag: &str) -> Vec<String> {
+    xml_elements(xml, tag)
+        .into_iter()
+        .map(xml_unescape)
+        .collect()
+}
+
+pub fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    xml_elements(xml, tag)
+        .first()
+        .map(|text| xml_unescape(text))
+}
+
+/// 替换第一个 `<tag>` 元素的内容，元素不存在时返回 None
+pub fn xml_replace(xml: &str, tag: &str, content: &str) -> Option<String> {
+    let inner = xml_elements(xml, tag).into_iter().next()?;
+    let start = inner.as_ptr() as usize - xml.as_ptr() as usize;
+    if inner.is_empty() && xml[..start].ends_with("/>") {
+        // 自闭合元素
+        let open = xml[..start].rfind(&format!("<{}", tag))?;
+        return Some(format!(
+            "{}<{tag}>{}</{tag}>{}",
+            &xml[..open],
+            content,
+            &xml[start..],
+            tag = tag
+        ));
+    }
+    Some(format!(
+        "{}{}{}",
+        &xml[..start],
+        content,
+        &xml[start + inner.len()..]
+    ))
+}
+
+pub fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_elements() {
+        let xml = r#"<List xmlns="x"><Items><Item>a&amp;b</Item><ItemCount>2</ItemCount><Item>c</Item><Item/></Items></List>"#;
+        assert_eq!(xml_texts(xml, "Item"), vec!["a&b", "c", ""]);
+        assert_eq!(xml_text(xml, "ItemCount").as_deref(), Some("2"));
+        assert_eq!(
+            xml_replace(xml, "ItemCount", "3").unwrap(),
+            xml.replace("<ItemCount>2<", "<ItemCount>3<")
+        );
+        assert_eq!(
+            xml_replace("<A><B/></A>", "B", "x").unwrap(),
+            "<A><B>x</B></A>"
+        );
+    }
+}
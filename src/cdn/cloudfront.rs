@@ -0,0 +1,269 @@
+use super::{CDN, HttpsConfig, HttpsOptions};
+use crate::aws::{AwsClient, xml_elements, xml_escape, xml_replace, xml_text, xml_texts};
+use crate::domain::{CloudProvider, Endpoint};
+use crate::{Result, error::AppError};
+use chrono::{DateTime, Utc};
+use reqwest::{Method, Url};
+use tracing::warn;
+
+const API_VERSION: &str = "2020-05-31";
+const DEFAULT_ENDPOINT: &str = "https://cloudfront.amazonaws.com";
+const LIST_DISTRIBUTIONS_LIMIT: &str = "100";
+const DEFAULT_MINIMUM_PROTOCOL_VERSION: &str = "TLSv1.2_2021";
+
+/// AWS CloudFront，证书 id 为 us-east-1 区域的 ACM 证书 ARN。
+/// `options.cloudfront_endpoint` 可覆盖接口地址
+pub struct CloudFrontCDN {
+    client: AwsClient,
+    endpoint: Endpoint,
+}
+
+/// ListDistributions 返回的分发摘要
+struct Distribution {
+    id: String,
+    aliases: Vec<String>,
+    certificate_arn: Option<String>,
+    http2: bool,
+    /// 默认缓存行为的 ViewerProtocolPolicy
+    viewer_protocol_policy: Option<String>,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl CloudFrontCDN {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        Ok(CloudFrontCDN {
+            client: AwsClient::new(provider),
+            endpoint: provider.endpoint("cloudfront_endpoint", DEFAULT_ENDPOINT)?,
+        })
+    }
+
+    fn url(&self, path: &str) -> Result<Url> {
+        self.endpoint.url(&format!("/{}/{}", API_VERSION, path))
+    }
+
+    async fn list_distributions(&self) -> Result<Vec<Distribution>> {
+        let mut distributions = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let mut url = self.url("distribution")?;
+            url.query_pairs_mut()
+                .append_pair("MaxItems", LIST_DISTRIBUTIONS_LIMIT);
+            if let Some(marker) = &marker {
+                url.query_pairs_mut().append_pair("Marker", marker);
+            }
+            let response = self
+                .client
+                .request("cloudfront", Method::GET, url, &[], String::new())
+                .await?;
+            distributions.extend(
+                xml_elements(&response.body, "DistributionSummary")
+                    .into_iter()
+                    .filter_map(distribution),
+            );
+            marker = xml_text(&response.body, "NextMarker");
+            if xml_text(&response.body, "IsTruncated").as_deref() != Some("true")
+                || marker.is_none()
+            {
+                break;
+            }
+        }
+        Ok(distributions)
+    }
+}
+
+#[async_trait::async_trait]
+impl CDN for CloudFrontCDN {
+    /// 读取分发配置，只替换 ViewerCertificate 及 `options` 中设置的项后带 ETag 回传
+    async fn update_ssl(
+        &self,
+        domain: &str,
+        cert_id: &str,
+        options: &HttpsOptions,
+    ) -> Result<String> {
+        let distribution = self
+            .list_distributions()
+            .await?
+            .into_iter()
+            .find(|distribution| distribution.aliases.iter().any(|alias| alias == domain))
+            .ok_or_else(|| {
+                AppError::CloudError(format!("cloudfront distribution not found: {}", domain))
+            })?;
+        if options.hsts.is_some() || options.ocsp_stapling.is_some() {
+            warn!(
+                "CloudFront does not support hsts or ocsp_stapling options, configure a response headers policy instead"
+            );
+        }
+
+        let url = self.url(&format!("distribution/{}/config", distribution.id))?;
+        let response = self
+            .client
+            .request("cloudfront", Method::GET, url.clone(), &[], String::new())
+            .await?;
+        let etag = response.etag.ok_or_else(|| {
+            AppError::CloudError("cloudfront distribution config etag missing".to_string())
+        })?;
+        let config = update_distribution_config(&response.body, cert_id, options)?;
+        let response = self
+            .client
+            .request(
+                "cloudfront",
+                Method::PUT,
+                url,
+                &[("content-type", "application/xml"), ("if-match", &etag)],
+                config,
+            )
+            .await?;
+        Ok(response.etag.unwrap_or_default())
+    }
+
+    async fn list_https(&self) -> Result<Vec<HttpsConfig>> {
+        Ok(self
+            .list_distributions()
+            .await?
+            .iter()
+            .flat_map(https_configs)
+            .collect())
+    }
+
+    async fn https_config(&self, domain: &str) -> Result<Option<HttpsConfig>> {
+        Ok(self
+            .list_https()
+            .await?
+            .into_iter()
+            .find(|config| config.domain == domain))
+    }
+}
+
+fn distribution(summary: &str) -> Option<Distribution> {
+    let viewer_certificate = xml_elements(summary, "ViewerCertificate")
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    Some(Distribution {
+        id: xml_text(summary, "Id")?,
+        aliases: xml_elements(summary, "Aliases")
+            .first()
+            .map(|aliases| xml_texts(aliases, "CNAME"))
+            .unwrap_or_default(),
+        certificate_arn: xml_text(viewer_certificate, "ACMCertificateArn"),
+        http2: xml_text(summary, "HttpVersion").is_some_and(|version| version.contains("http2")),
+        viewer_protocol_policy: xml_elements(summary, "DefaultCacheBehavior")
+            .first()
+            .and_then(|behavior| xml_text(behavior, "ViewerProtocolPolicy")),
+        last_modified: xml_text(summary, "LastModifiedTime")
+            .and_then(|time| DateTime::parse_from_rfc3339(&time).ok())
+            .map(|time| time.with_timezone(&Utc)),
+    })
+}
+
+/// 分发的每个备用域名对应一条配置
+fn https_configs(distribution: &Distribution) -> Vec<HttpsConfig> {
+    distribution
+        .aliases
+        .iter()
+        .map(|alias| HttpsConfig {
+            domain: alias.clone(),
+            https: distribution.certificate_arn.is_some(),
+            http2: distribution.http2,
+            hsts: false,
+            certificate_id: distribution.certificate_arn.clone(),
+            expires_at: None,
+            deployed_at: distribution.last_modified,
+            // https-only 无法用 force_redirect 表示，回滚时保持不变
+            options: HttpsOptions {
+                http2: Some(distribution.http2),
+                force_redirect: match distribution.viewer_protocol_policy.as_deref() {
+                    Some("redirect-to-https") => Some(true),
+                    Some("allow-all") => Some(false),
+                    _ => None,
+                },
+                ..Default::default()
+            },
+        })
+        .collect()
+}
+
+/// 替换分发配置中的 ViewerCertificate，保留原有的 SSLSupportMethod 和 MinimumProtocolVersion
+fn update_distribution_config(
+    config: &str,
+    certificate_arn: &str,
+    options: &HttpsOptions,
+) -> Result<String> {
+    let viewer_certificate = xml_elements(config, "ViewerCertificate")
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let ssl_support_method =
+        xml_text(viewer_certificate, "SSLSupportMethod").unwrap_or_else(|| "sni-only".to_string());
+    let minimum_protocol_version = xml_text(viewer_certificate, "MinimumProtocolVersion")
+        .unwrap_or_else(|| DEFAULT_MINIMUM_PROTOCOL_VERSION.to_string());
+    let viewer_certificate = format!(
+        "<CloudFrontDefaultCertificate>false</CloudFrontDefaultCertificate><ACMCertificateArn>{}</ACMCertificateArn><SSLSupportMethod>{}</SSLSupportMethod><MinimumProtocolVersion>{}</MinimumProtocolVersion><CertificateSource>acm</CertificateSource>",
+        xml_escape(certificate_arn),
+        ssl_support_method,
+        minimum_protocol_version
+    );
+    let mut config =
+        xml_replace(config, "ViewerCertificate", &viewer_certificate).ok_or_else(|| {
+            AppError::CloudError("cloudfront ViewerCertificate not found".to_string())
+        })?;
+
+    if let Some(http2) = options.http2 {
+        let current = xml_text(&config, "HttpVersion").unwrap_or_default();
+        let version = match http2 {
+            true if current.contains("http2") => current,
+            true => "http2".to_string(),
+            false => "http1.1".to_string(),
+        };
+        if let Some(updated) = xml_replace(&config, "HttpVersion", &version) {
+            config = updated;
+        }
+    }
+    if let Some(force_redirect) = options.force_redirect {
+        let policy = if force_redirect {
+            "redirect-to-https"
+        } else {
+            "allow-all"
+        };
+        if let Some(behavior) = xml_elements(&config, "DefaultCacheBehavior").first()
+            && let Some(behavior) = xml_replace(behavior, "ViewerProtocolPolicy", policy)
+            && let Some(updated) = xml_replace(&config, "DefaultCacheBehavior", &behavior)
+        {
+            config = updated;
+        }
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_distribution_config() {
+        let config = r#"<?xml version="1.0" encoding="UTF-8"?>
+<DistributionConfig xmlns="http://cloudfront.amazonaws.com/doc/2020-05-31/"><DefaultCacheBehavior><TargetOriginId>origin</TargetOriginId><ViewerProtocolPolicy>allow-all</ViewerProtocolPolicy></DefaultCacheBehavior><CacheBehaviors><Quantity>1</Quantity><Items><CacheBehavior><ViewerProtocolPolicy>allow-all</ViewerProtocolPolicy></CacheBehavior></Items></CacheBehaviors><ViewerCertificate><CloudFrontDefaultCertificate>false</CloudFrontDefaultCertificate><ACMCertificateArn>arn:old</ACMCertificateArn><SSLSupportMethod>sni-only</SSLSupportMethod><MinimumProtocolVersion>TLSv1.2_2019</MinimumProtocolVersion><CertificateSource>acm</CertificateSource></ViewerCertificate><HttpVersion>http2and3</HttpVersion></DistributionConfig>"#;
+        let options = HttpsOptions {
+            http2: Some(true),
+            force_redirect: Some(true),
+            ..Default::default()
+        };
+        let updated = update_distribution_config(config, "arn:new", &options).unwrap();
+        assert_eq!(
+            xml_text(&updated, "ACMCertificateArn").as_deref(),
+            Some("arn:new")
+        );
+        assert_eq!(
+            xml_text(&updated, "MinimumProtocolVersion").as_deref(),
+            Some("TLSv1.2_2019")
+        );
+        assert_eq!(
+            xml_text(&updated, "HttpVersion").as_deref(),
+            Some("http2and3")
+        );
+        assert_eq!(
+            xml_texts(&updated, "ViewerProtocolPolicy"),
+            vec!["redirect-to-https", "allow-all"]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use super::{CDN, DEFAULT_HSTS_MAX_AGE, HttpsConfig, HttpsOptions, is_on, switch};
+use crate::Result;
+use crate::domain::{CloudProvider, Endpoint};
+use crate::huawei::HuaweiClient;
+use chrono::DateTime;
+use reqwest::{Method, Url};
+use serde_json::{Value, json};
+
+const DEFAULT_ENDPOINT: &str = "https://cdn.myhuaweicloud.com";
+const LIST_DOMAINS_LIMIT: usize = 1000;
+/// 证书来源：2 为 SCM 证书，通过 `scm_certificate_id` 引用
+const CERTIFICATE_SOURCE_SCM: u64 = 2;
+
+/// 华为云 CDN，证书 id 为 SCM 中的证书 id。`options.cdn_endpoint` 可覆盖接口地址
+pub struct HuaweiCDN {
+    client: HuaweiClient,
+    endpoint: Endpoint,
+}
+
+impl HuaweiCDN {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        Ok(HuaweiCDN {
+            client: HuaweiClient::new(provider),
+            endpoint: provider.endpoint("cdn_endpoint", DEFAULT_ENDPOINT)?,
+        })
+    }
+
+    /// 列出账号中的加速域名，`domain` 不为空时只查询该域名
+    async fn list_domains(&self, domain: Option<&str>) -> Result<Vec<String>> {
+        let mut domains = Vec::new();
+        let mut page_number = 1;
+        loop {
+            let mut url = self.endpoint.url("/v1.0/cdn/domains")?;
+            url.query_pairs_mut()
+                .append_pair("page_size", &LIST_DOMAINS_LIMIT.to_string())
+                .append_pair("page_number", &page_number.to_string())
+                .append_pair("enterprise_project_id", "ALL");
+            if let Some(domain) = domain {
+                url.query_pairs_mut().append_pair("domain_name", domain);
+            }
+            let response = self.client.request(Method::GET, url, None).await?;
+            let page: Vec<String> = response["domains"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|domain| domain["domain_name"].as_str())
+                .map(str::to_string)
+                .collect();
+            let done = page.len() < LIST_DOMAINS_LIMIT;
+            domains.extend(page);
+            if done {
+                break;
+            }
+            page_number += 1;
+        }
+        Ok(domains)
+    }
+
+    fn configs_url(&self, domain: &str) -> Result<Url> {
+        self.endpoint.url(&format!(
+            "/v1.1/cdn/configuration/domains/{}/configs",
+            domain
+        ))
+    }
+
+    async fn configs(&self, domain: &str) -> Result<Value> {
+        let response = self
+            .client
+            .request(Method::GET, self.configs_url(domain)?, None)
+            .await?;
+        Ok(response["configs"].clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl CDN for HuaweiCDN {
+    /// 修改 HTTPS 配置时需要完整传入 https 对象，先读取当前配置再替换证书
+    async fn update_ssl(
+        &self,
+        domain: &str,
+        cert_id: &str,
+        options: &HttpsOptions,
+    ) -> Result<String> {
+        let current = self.configs(domain).await?;
+        let body = json!({ "configs": update_configs(&current, cert_id, options) });
+        let response = self
+            .client
+            .request(Method::PUT, self.configs_url(domain)?, Some(&body))
+            .await?;
+        Ok(response["configs"]["https"]["scm_certificate_id"]
+            .as_str()
+            .unwrap_or(cert_id)
+            .to_string())
+    }
+
+    async fn list_https(&self) -> Result<Vec<HttpsConfig>> {
+        let mut configs = Vec::new();
+        for domain in self.list_domains(None).await? {
+            let current = self.configs(&domain).await?;
+            configs.push(https_config(&domain, &current));
+        }
+        Ok(configs)
+    }
+
+    async fn https_config(&self, domain: &str) -> Result<Option<HttpsConfig>> {
+        if !self
+            .list_domains(Some(domain))
+            .await?
+            .iter()
+            .any(|name| name == domain)
+        {
+            return Ok(None);
+        }
+        let current = self.configs(domain).await?;
+        Ok(Some(https_config(domain, &current)))
+    }
+}
+
+fn https_config(domain: &str, configs: &Value) -> HttpsConfig {
+    let https = &configs["https"];
+    HttpsConfig {
+        domain: domain.to_string(),
+        https: is_on(&https["https_status"]),
+        http2: is_on(&https["http2_status"]),
+        hsts: is_on(&configs["hsts"]["status"]),
+        certificate_id: https["scm_certificate_id"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string),
+        expires_at: https["expire_time"]
+            .as_i64()
+            .and_then(DateTime::from_timestamp_millis),
+        deployed_at: None,
+        options: HttpsOptions {
+            http2: Some(is_on(&https["http2_status"])),
+            ocsp_stapling: Some(is_on(&https["ocsp_stapling_status"])),
+            force_redirect: Some(is_on(&configs["force_redirect"]["status"])),
+            hsts: Some(is_on(&configs["hsts"]["status"])),
+            hsts_max_age: configs["hsts"]["max_age"].as_i64(),
+        },
+    }
+}
+
+/// 生成修改配置的请求，https 中保留原有的 HTTP/2、OCSP Stapling 和 TLS 版本，
+/// 强制跳转和 HSTS 只在 `options` 中设置时修改
+fn update_configs(current: &Value, cert_id: &str, options: &HttpsOptions) -> Value {
+    let https = &current["https"];
+    let http2 = options
+        .http2
+        .unwrap_or_else(|| is_on(&https["http2_status"]));
+    let ocsp_stapling = options
+        .ocsp_stapling
+        .unwrap_or_else(|| is_on(&https["ocsp_stapling_status"]));
+    let mut put_https = json!({
+        "https_status": "on",
+        "certificate_name": cert_id,
+        "certificate_source": CERTIFICATE_SOURCE_SCM,
+        "scm_certificate_id": cert_id,
+        "http2_status": switch(http2),
+        "ocsp_stapling_status": switch(ocsp_stapling),
+    });
+    for key in ["certificate_type", "tls_version"] {
+        if let Some(value) = https[key].as_str().filter(|value| !value.is_empty()) {
+            put_https[key] = json!(value);
+        }
+    }
+    let mut configs = json!({ "https": put_https });
+
+    if let Some(enabled) = options.force_redirect {
+        let mut force_redirect = current["force_redirect"].clone();
+        if !force_redirect.is_object() {
+            force_redirect = json!({});
+        }
+        force_redirect["status"] = json!(switch(enabled));
+        if enabled {
+            if force_redirect["type"].as_str().is_none() {
+                force_redirect["type"] = json!("https");
+            }
+            if force_redirect["redirect_code"].as_u64().is_none() {
+                force_redirect["redirect_code"] = json!(302);
+            }
+        }
+        configs["force_redirect"] = force_redirect;
+    }
+    if let Some(enabled) = options.hsts {
+        let mut hsts = current["hsts"].clone();
+        if !hsts.is_object() {
+            hsts = json!({});
+        }
+        hsts["status"] = json!(switch(enabled));
+        if let Some(max_age) = options.hsts_max_age {
+            hsts["max_age"] = json!(max_age);
+        } else if enabled && hsts["max_age"].as_i64().is_none() {
+            hsts["max_age"] = json!(DEFAULT_HSTS_MAX_AGE);
+        }
+        configs["hsts"] = hsts;
+    }
+    configs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_configs() {
+        let current = json!({
+            "https": {
+                "https_status": "on",
+                "certificate_name": "old",
+                "certificate_source": 0,
+                "http2_status": "on",
+                "ocsp_stapling_status": "off",
+                "tls_version": "TLSv1.2,TLSv1.3"
+            },
+            "force_redirect": { "status": "off" },
+            "hsts": { "status": "off", "max_age": 600 }
+        });
+        let options = HttpsOptions {
+            force_redirect: Some(true),
+            hsts: Some(true),
+            ..Default::default()
+        };
+        let configs = update_configs(&current, "scs123", &options);
+        assert_eq!(
+            configs["https"],
+            json!({
+                "https_status": "on",
+                "certificate_name": "scs123",
+                "certificate_source": 2,
+                "scm_certificate_id": "scs123",
+                "http2_status": "on",
+                "ocsp_stapling_status": "off",
+                "tls_version": "TLSv1.2,TLSv1.3"
+            })
+        );
+        assert_eq!(
+            configs["force_redirect"],
+            json!({ "status": "on", "type": "https", "redirect_code": 302 })
+        );
+        assert_eq!(configs["hsts"], json!({ "status": "on", "max_age": 600 }));
+    }
+}
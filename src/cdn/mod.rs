@@ -1,10 +1,65 @@
+mod cloudfront;
+mod huawei;
 mod tencent;
 
+pub use cloudfront::CloudFrontCDN;
+pub use huawei::HuaweiCDN;
 pub use tencent::TencentCDN;
 
 use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// 开启 HSTS 且未指定有效期时使用一年
+const DEFAULT_HSTS_MAX_AGE: i64 = 31536000;
+
+/// CDN 域名当前的 HTTPS 配置
+#[derive(Debug, Clone, Default)]
+pub struct HttpsConfig {
+    pub domain: String,
+    pub https: bool,
+    pub http2: bool,
+    pub hsts: bool,
+    pub certificate_id: Option<String>,
+    /// 服务商记录的证书到期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    pub deployed_at: Option<DateTime<Utc>>,
+    /// 当前生效的 HTTPS 选项，部署失败回滚时与原证书一起恢复；服务商不支持的项为 None
+    pub options: HttpsOptions,
+}
+
+/// 部署证书时显式设置的 HTTPS 选项，未设置的项保持 CDN 当前配置
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct HttpsOptions {
+    pub http2: Option<bool>,
+    pub ocsp_stapling: Option<bool>,
+    pub force_redirect: Option<bool>,
+    pub hsts: Option<bool>,
+    pub hsts_max_age: Option<i64>,
+}
 
 #[async_trait::async_trait]
 pub trait CDN: Send + Sync {
-    async fn update_ssl(&self, domain: &str, cert_id: &str) -> Result<String>;
+    /// 更换证书，保留其他 HTTPS 配置，仅修改 `options` 中设置的项
+    async fn update_ssl(
+        &self,
+        domain: &str,
+        cert_id: &str,
+        options: &HttpsOptions,
+    ) -> Result<String>;
+    /// 列出账号中全部 CDN 域名的 HTTPS 配置
+    async fn list_https(&self) -> Result<Vec<HttpsConfig>>;
+    /// 查询单个 CDN 域名的 HTTPS 配置，域名不存在时返回 None
+    async fn https_config(&self, domain: &str) -> Result<Option<HttpsConfig>>;
+}
+
+/// 腾讯云和华为云 CDN 的开关字段取值为 `on` 或 `off`
+fn is_on(switch: &Value) -> bool {
+    switch.as_str() == Some("on")
+}
+
+fn switch(enabled: bool) -> &'static str {
+    if enabled { "on" } else { "off" }
 }
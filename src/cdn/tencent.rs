@@ -1,37 +1,238 @@
 use crate::{Result, error::AppError};
 
-use super::CDN;
-use tencent_sdk::{
-    client::TencentCloudAsync, core::TencentCloudResult, middleware::RetryAsync,
-    services::cdn::UpdateDomainConfig, transport::async_impl::ReqwestAsync,
-};
+use super::{CDN, DEFAULT_HSTS_MAX_AGE, HttpsConfig, HttpsOptions, is_on, switch};
+use crate::ssl::parse_tencent_time;
+use crate::tencent::{Action, TencentClient};
+use serde_json::{Value, json};
 
+const CDN_API_VERSION: &str = "2018-06-06";
+/// DescribeDomainsConfig 单页最多返回 1000 条
+const DESCRIBE_DOMAINS_LIMIT: u64 = 1000;
+
+/// 腾讯云 CDN。修改 HTTPS 配置需要完整回传 Https 对象，固定版本的 SDK 不支持，通过 [`TencentClient`] 调用
 pub struct TencentCDN {
-    pub client: TencentCloudAsync<RetryAsync<ReqwestAsync>>,
+    api: TencentClient,
 }
 
 impl TencentCDN {
-    pub fn new(secret_id: &str, secret_key: &str) -> TencentCloudResult<Self> {
-        let client = TencentCloudAsync::builder(secret_id, secret_key)?
-            .no_system_proxy() // optional convenience helper
-            .with_default_region("ap-guangzhou")
-            .with_retry(3, std::time::Duration::from_millis(200))
-            .build()?;
-
-        Ok(TencentCDN { client })
+    pub fn new(secret_id: &str, secret_key: &str) -> Result<Self> {
+        Ok(TencentCDN {
+            api: TencentClient::new(secret_id, secret_key),
+        })
     }
 }
 
 #[async_trait::async_trait]
 impl CDN for TencentCDN {
-    async fn update_ssl(&self, domain: &str, cert_id: &str) -> Result<String> {
-        let request = UpdateDomainConfig::new(domain, cert_id);
-        match self.client.request(&request).await {
-            Ok(response) => Ok(response.response.request_id),
+    async fn update_ssl(
+        &self,
+        domain: &str,
+        cert_id: &str,
+        options: &HttpsOptions,
+    ) -> Result<String> {
+        // 只传证书 id 时其他 HTTPS 配置会被重置，需要先读取当前配置并完整回传
+        let detail = self
+            .describe_domains(Some(domain))
+            .await?
+            .into_iter()
+            .find(|detail| detail["Domain"].as_str() == Some(domain))
+            .ok_or_else(|| {
+                AppError::CloudError(format!("tencent cloud cdn domain not found: {}", domain))
+            })?;
+
+        let mut payload = update_domain_config(&detail, cert_id, options);
+        payload["Domain"] = json!(domain);
+        match self
+            .api
+            .request(&action("UpdateDomainConfig"), &payload)
+            .await
+        {
+            Ok(response) => Ok(response["RequestId"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()),
             Err(e) => Err(AppError::CloudError(format!(
-                "tencent cloud download ssl certificate failed:{}",
+                "tencent cloud update cdn ssl certificate failed:{}",
                 e
             ))),
         }
     }
+
+    async fn list_https(&self) -> Result<Vec<HttpsConfig>> {
+        let details = self.describe_domains(None).await?;
+        Ok(details.iter().filter_map(https_config).collect())
+    }
+
+    async fn https_config(&self, domain: &str) -> Result<Option<HttpsConfig>> {
+        let details = self.describe_domains(Some(domain)).await?;
+        Ok(details
+            .iter()
+            .filter_map(https_config)
+            .find(|config| config.domain == domain))
+    }
+}
+
+impl TencentCDN {
+    /// 分页查询 DescribeDomainsConfig，`domain` 不为空时只查询该域名
+    async fn describe_domains(&self, domain: Option<&str>) -> Result<Vec<Value>> {
+        let mut details = Vec::new();
+        let mut offset = 0;
+        loop {
+            let mut payload = json!({ "Offset": offset, "Limit": DESCRIBE_DOMAINS_LIMIT });
+            if let Some(domain) = domain {
+                payload["Filters"] = json!([{ "Name": "domain", "Value": [domain] }]);
+            }
+            let response = self
+                .api
+                .request(&action("DescribeDomainsConfig"), &payload)
+                .await?;
+            let page = response["Domains"].as_array().cloned().unwrap_or_default();
+            let total_number = response["TotalNumber"].as_u64().unwrap_or_default();
+            offset += page.len() as u64;
+            let page_empty = page.is_empty();
+            details.extend(page);
+
+            if page_empty || offset >= total_number {
+                break;
+            }
+        }
+        Ok(details)
+    }
+}
+
+fn action(action: &str) -> Action<'_> {
+    Action {
+        service: "cdn",
+        version: CDN_API_VERSION,
+        action,
+    }
+}
+
+/// 生成 UpdateDomainConfig 的 Https 和 ForceRedirect，保留当前配置中未在 `options` 设置的项
+fn update_domain_config(detail: &Value, cert_id: &str, options: &HttpsOptions) -> Value {
+    let mut https = detail["Https"].clone();
+    if !https.is_object() {
+        https = json!({});
+    }
+    // SslStatus 只读，CertInfo 中只需传证书 id
+    if let Some(https) = https.as_object_mut() {
+        https.remove("SslStatus");
+    }
+    https["Switch"] = json!(switch(true));
+    https["CertInfo"] = json!({ "CertId": cert_id });
+    if let Some(http2) = options.http2 {
+        https["Http2"] = json!(switch(http2));
+    }
+    if let Some(ocsp_stapling) = options.ocsp_stapling {
+        https["OcspStapling"] = json!(switch(ocsp_stapling));
+    }
+    if let Some(enabled) = options.hsts {
+        let mut hsts = https["Hsts"].clone();
+        if !hsts.is_object() {
+            hsts = json!({});
+        }
+        hsts["Switch"] = json!(switch(enabled));
+        if let Some(max_age) = options.hsts_max_age {
+            hsts["MaxAge"] = json!(max_age);
+        } else if enabled && hsts["MaxAge"].as_i64().is_none() {
+            hsts["MaxAge"] = json!(DEFAULT_HSTS_MAX_AGE);
+        }
+        https["Hsts"] = hsts;
+    }
+    let mut payload = json!({ "Https": https });
+
+    if let Some(enabled) = options.force_redirect {
+        let mut force_redirect = detail["ForceRedirect"].clone();
+        if !force_redirect.is_object() {
+            force_redirect = json!({});
+        }
+        force_redirect["Switch"] = json!(switch(enabled));
+        if enabled {
+            if force_redirect["RedirectType"].as_str().is_none() {
+                force_redirect["RedirectType"] = json!("https");
+            }
+            if force_redirect["RedirectStatusCode"].as_i64().is_none() {
+                force_redirect["RedirectStatusCode"] = json!(302);
+            }
+        }
+        payload["ForceRedirect"] = force_redirect;
+    }
+    payload
+}
+
+fn https_config(detail: &Value) -> Option<HttpsConfig> {
+    let domain = detail["Domain"].as_str()?.to_string();
+    let https = &detail["Https"];
+    let cert_info = &https["CertInfo"];
+    Some(HttpsConfig {
+        domain,
+        https: is_on(&https["Switch"]),
+        http2: is_on(&https["Http2"]),
+        hsts: is_on(&https["Hsts"]["Switch"]),
+        certificate_id: cert_info["CertId"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string),
+        expires_at: cert_info["ExpireTime"]
+            .as_str()
+            .and_then(parse_tencent_time),
+        deployed_at: cert_info["DeployTime"]
+            .as_str()
+            .and_then(parse_tencent_time),
+        options: HttpsOptions {
+            http2: Some(is_on(&https["Http2"])),
+            ocsp_stapling: Some(is_on(&https["OcspStapling"])),
+            force_redirect: Some(is_on(&detail["ForceRedirect"]["Switch"])),
+            hsts: Some(is_on(&https["Hsts"]["Switch"])),
+            hsts_max_age: https["Hsts"]["MaxAge"].as_i64(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_domain_config() {
+        let detail = json!({
+            "Domain": "www.example.com",
+            "Https": {
+                "Switch": "on",
+                "Http2": "on",
+                "OcspStapling": "off",
+                "SslStatus": "deployed",
+                "TlsVersion": ["TLSv1.2", "TLSv1.3"],
+                "CertInfo": { "CertId": "old", "ExpireTime": "2025-01-01 08:00:00" },
+                "Hsts": { "Switch": "off", "MaxAge": 600 }
+            },
+            "ForceRedirect": { "Switch": "off" }
+        });
+        let options = HttpsOptions {
+            force_redirect: Some(true),
+            hsts: Some(true),
+            ..Default::default()
+        };
+        let payload = update_domain_config(&detail, "new", &options);
+        assert_eq!(
+            payload["Https"],
+            json!({
+                "Switch": "on",
+                "Http2": "on",
+                "OcspStapling": "off",
+                "TlsVersion": ["TLSv1.2", "TLSv1.3"],
+                "CertInfo": { "CertId": "new" },
+                "Hsts": { "Switch": "on", "MaxAge": 600 }
+            })
+        );
+        assert_eq!(
+            payload["ForceRedirect"],
+            json!({ "Switch": "on", "RedirectType": "https", "RedirectStatusCode": 302 })
+        );
+        let config = https_config(&detail).unwrap();
+        assert_eq!(config.certificate_id.as_deref(), Some("old"));
+        assert!(config.http2 && !config.hsts);
+        assert_eq!(config.options.force_redirect, Some(false));
+        assert_eq!(config.options.hsts_max_age, Some(600));
+    }
 }
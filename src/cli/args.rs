@@ -1,5 +1,5 @@
 // src/cli.rs
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "csu")]
@@ -11,6 +11,14 @@ pub struct Cli {
     /// Domains to manage, each line a domain
     #[arg(short, long)]
     pub domains: String,
+
+    /// Directory for run state, defaults to `.csu` next to the domains file
+    #[arg(short, long)]
+    pub store: Option<String>,
+
+    /// Credential profiles file, defaults to `profiles.json` next to the domains file
+    #[arg(long)]
+    pub profiles: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -21,6 +29,90 @@ pub enum Commands {
     Update,
     /// force update ssl certificates
     ForceUpdate,
+    /// export ssl certificate as pem, pkcs12 and jks files
+    Export {
+        /// Domain name in the domains file
+        domain: String,
+        /// Output directory
+        #[arg(short, long, default_value = ".")]
+        output: String,
+        /// Also write pkcs12 and jks files, the password is read from CSU_EXPORT_PASSWORD
+        #[arg(short, long)]
+        keystore: bool,
+        /// Certificate id, defaults to the last certificate this tool deployed to all targets
+        #[arg(short, long)]
+        certificate_id: Option<String>,
+    },
+    /// import an externally issued certificate and deploy it to the domain's targets
+    Import {
+        /// Domain name in the domains file
+        #[arg(long)]
+        domain: String,
+        /// Certificate chain file in pem format
+        #[arg(long)]
+        cert: String,
+        /// Private key file in pem format
+        #[arg(long)]
+        key: String,
+    },
+    /// revoke a certificate by domain name or certificate id
+    Revoke {
+        /// Domain name in the domains file, or a certificate id
+        target: String,
+        /// Revoke reason
+        #[arg(short, long, default_value = "key compromise")]
+        reason: String,
+        /// Issue and deploy a replacement certificate right after revoking
+        #[arg(long)]
+        replace: bool,
+    },
+    /// discover cdn domains in provider accounts and generate domains file entries
+    Discover {
+        /// Profiles of CDN accounts to scan, defaults to all profiles of CDN providers
+        #[arg(long)]
+        cdn: Vec<String>,
+        /// Profiles of DNS accounts used to find root zones, defaults to all profiles of DNS providers
+        #[arg(long)]
+        dns: Vec<String>,
+        /// Profile used as ssl_provider, required when a CDN account cannot issue certificates
+        #[arg(long)]
+        ssl: Option<String>,
+        /// Merge into the domains file instead of printing
+        #[arg(short, long)]
+        write: bool,
+    },
+    /// manage certificates in ssl provider accounts
+    Certs {
+        #[command(subcommand)]
+        command: CertsCommands,
+    },
     /// Show tool version
     Version,
 }
+
+#[derive(Subcommand)]
+pub enum CertsCommands {
+    /// list certificates in every ssl provider account of the domains file
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// delete expired and superseded certificates that are no longer in use
+    Prune {
+        /// Domain name in the domains file, defaults to all domains
+        domain: Option<String>,
+        /// Number of most recent certificates to keep for each domain
+        #[arg(short, long, default_value_t = 1)]
+        keep: usize,
+        /// Only show certificates that would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
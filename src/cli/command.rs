@@ -1,13 +1,30 @@
 use crate::{
-    domain::{Domain, auto_update_ssl},
-    ssl::check_ssl_certificate,
+    deploy::IssuedCertificate,
+    domain::{
+        CDN_PROVIDERS, CloudProvider, DNS_PROVIDERS, Domain, SSL_PROVIDERS, auto_update_ssl,
+        deploy_certificate,
+    },
+    error::AppError,
+    profile::Profiles,
+    report::{DeployResult, PruneResult},
+    ssl::{
+        CertificateSummary, RevokeStatus, build_jks, build_pkcs12, check_ssl_certificate,
+        export_password, name_covered, parse_cert_from_pem, verify_certificate_names, write_file,
+        write_pem_files,
+    },
+    store::Store,
+    zone::{find_zone, resolve_zones},
 };
 use futures::StreamExt;
 use futures::future::join_all;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use tracing::info;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
 
 pub async fn check_ssl_remin_days(domains: Vec<Domain>) -> crate::Result<Vec<Domain>> {
     let (input_tx, input_rx) = mpsc::unbounded_channel();
@@ -26,7 +43,19 @@ pub async fn check_ssl_remin_days(domains: Vec<Domain>) -> crate::Result<Vec<Dom
         .for_each_concurrent(domains_len, |mut domain| {
             let output_tx = c_output.clone();
             async move {
-                if let Ok(info) = check_ssl_certificate(&domain.name) {
+                // 优先读取 CDN 接口记录的证书，无法读取时连接域名检查
+                let info = match domain.cdn_certificate_info().await {
+                    Ok(Some(info)) => Ok(info),
+                    Ok(None) => check_ssl_certificate(&domain.name),
+                    Err(e) => {
+                        warn!(
+                            "Failed to query CDN certificate for domain {}: {}",
+                            domain.name, e
+                        );
+                        check_ssl_certificate(&domain.name)
+                    }
+                };
+                if let Ok(info) = info {
                     domain.certificate_info = Some(info);
                     let _ = output_tx.send(domain);
                 }
@@ -61,7 +90,8 @@ pub async fn apply_ssl_certificate(domains: Vec<Domain>) -> crate::Result<Vec<Do
             let output_tx = c_output.clone();
             async move {
                 let mut domain = domain;
-                if domain.apply_ssl("DNS").await.is_ok() {
+                let method = domain.validation_method();
+                if domain.apply_ssl(method).await.is_ok() {
                     let _ = output_tx.send(domain);
                 }
             }
@@ -77,26 +107,434 @@ pub async fn apply_ssl_certificate(domains: Vec<Domain>) -> crate::Result<Vec<Do
     Ok(info)
 }
 
-pub async fn update_ssl_certificate(domains: Vec<Domain>) -> crate::Result<()> {
-    let mut tasks: Vec<JoinHandle<crate::Result<()>>> = Vec::with_capacity(domains.len());
+/// 并发更新域名证书，`force` 为 true 时总是申请新证书
+pub async fn update_ssl_certificate(
+    mut domains: Vec<Domain>,
+    store: &Store,
+    force: bool,
+) -> crate::Result<Vec<DeployResult>> {
+    // 只有需要更新的域名才会添加验证记录，在此时才确定 DNS 根域名
+    resolve_zones(&mut domains, store).await?;
+    let mut tasks: Vec<JoinHandle<Vec<DeployResult>>> = Vec::with_capacity(domains.len());
 
     for domain in domains {
-        let handle: JoinHandle<crate::Result<()>> = tokio::spawn(async move {
-            match auto_update_ssl(domain.clone()).await {
-                Ok(_) => {
+        let store = store.clone();
+        let handle: JoinHandle<Vec<DeployResult>> = tokio::spawn(async move {
+            match auto_update_ssl(domain.clone(), store, force).await {
+                Ok(results) => {
                     info!("Successfully updated SSL for domain: {}", domain.name());
-                    Ok(())
+                    results
                 }
                 Err(e) => {
                     info!("Failed to update SSL for domain: {}: {}", domain.name(), e);
-                    Err(e)
+                    vec![DeployResult::failure(
+                        &domain.name,
+                        "ssl",
+                        &domain.name,
+                        &e.to_string(),
+                    )]
                 }
             }
         });
         tasks.push(handle);
     }
 
-    let _ = join_all(tasks).await;
+    let results = join_all(tasks)
+        .await
+        .into_iter()
+        .filter_map(|result| result.ok())
+        .flatten()
+        .collect();
 
-    Ok(())
+    Ok(results)
+}
+
+pub async fn export_certificate(
+    domain: &Domain,
+    store: &Store,
+    certificate_id: Option<String>,
+    output: &str,
+    keystore: bool,
+) -> crate::Result<Vec<PathBuf>> {
+    // 先读取密码，缺少时不写出任何文件
+    let password = if keystore {
+        Some(export_password()?)
+    } else {
+        None
+    };
+    let certificate_id = match certificate_id {
+        Some(certificate_id) => certificate_id,
+        None => store.current_certificate_id(&domain.name)?.ok_or_else(|| {
+            AppError::Other(format!(
+                "no certificate recorded for domain {}, please specify --certificate-id",
+                domain.name
+            ))
+        })?,
+    };
+    info!(
+        "Exporting SSL certificate {} for domain {}",
+        certificate_id, domain.name
+    );
+    let bundle = domain.download_certificate(&certificate_id).await?;
+
+    let output = Path::new(output);
+    let name = domain.name.replace('*', "_");
+    let mut paths = write_pem_files(&bundle, output, &name)?;
+    if let Some(password) = password {
+        let path = output.join(format!("{}.p12", name));
+        write_file(
+            &path,
+            &build_pkcs12(&bundle, &domain.name, &password)?,
+            true,
+        )?;
+        paths.push(path);
+
+        let path = output.join(format!("{}.jks", name));
+        write_file(&path, &build_jks(&bundle, &domain.name, &password)?, true)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// 导入外部签发的证书并部署到域名的全部目标，CDN 目标会先上传到对应账号
+pub async fn import_certificate(
+    domain: &Domain,
+    cert_path: &str,
+    key_path: &str,
+) -> crate::Result<Vec<DeployResult>> {
+    let certificate = std::fs::read(cert_path)?;
+    let private_key = Zeroizing::new(std::fs::read(key_path)?);
+    let bundle = parse_cert_from_pem(&certificate, &private_key)?;
+    let names = domain.names();
+    verify_certificate_names(&bundle, &names)?;
+    info!(
+        "Importing SSL certificate {} for domain {}",
+        cert_path, domain.name
+    );
+
+    let cert = IssuedCertificate {
+        source: None,
+        names,
+        bundle,
+    };
+    deploy_certificate(domain, &cert, None).await
+}
+
+/// 列出域名配置中全部不同 ssl_provider 账号下的证书，并标注使用该证书的 CDN 域名
+pub async fn list_certificates(domains: &[Domain]) -> crate::Result<Vec<CertificateSummary>> {
+    let mut providers: Vec<&CloudProvider> = Vec::new();
+    for domain in domains {
+        if !providers.contains(&&domain.ssl_provider) {
+            providers.push(&domain.ssl_provider);
+        }
+    }
+
+    // 单个账号查询失败时跳过，不影响其他账号
+    let mut certificates = Vec::new();
+    for provider in providers {
+        match provider.list_certificates().await {
+            Ok(account_certificates) => certificates.extend(account_certificates),
+            Err(e) => warn!(
+                "Failed to list certificates for account {}: {}",
+                provider.account(),
+                e
+            ),
+        }
+    }
+    Ok(certificates)
+}
+
+/// 清理域名的过期和已被替换的证书，`dry_run` 时只列出待删除的证书
+pub async fn prune_certificates(
+    domains: &[Domain],
+    store: &Store,
+    keep: usize,
+    dry_run: bool,
+) -> crate::Result<Vec<PruneResult>> {
+    let mut results = Vec::new();
+    for domain in domains {
+        match domain.prune_certificates(store, keep, dry_run).await {
+            Ok(domain_results) => results.extend(domain_results),
+            Err(e) => {
+                warn!(
+                    "Failed to prune certificates for domain {}: {}",
+                    domain.name, e
+                );
+                results.push(PruneResult::failure(
+                    &domain.name,
+                    "-",
+                    "-",
+                    "-",
+                    &e.to_string(),
+                ));
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// 吊销证书，`target` 为域名配置中的域名（吊销本工具当前部署的证书）或证书 id。
+/// `replace` 时立即通过更新流程签发并部署新证书
+pub async fn revoke_certificate(
+    domains: &[Domain],
+    store: &Store,
+    target: &str,
+    reason: &str,
+    replace: bool,
+) -> crate::Result<Vec<DeployResult>> {
+    let (domain, certificate_id, provider) = resolve_revoke_target(domains, store, target).await?;
+    let domain_name = domain
+        .as_ref()
+        .map(|domain| domain.name.clone())
+        .unwrap_or_else(|| "-".to_string());
+
+    let ssl_client = crate::ssl_client(&provider)?;
+    let mut results = match ssl_client.revoke(&certificate_id, reason).await {
+        // 验证完成前证书仍然有效，不记录为已吊销
+        Ok(RevokeStatus::PendingValidation(auths)) => {
+            warn!(
+                "Revoke SSL certificate {} for domain {} is pending validation",
+                certificate_id, domain_name
+            );
+            vec![DeployResult::failure(
+                &domain_name,
+                "revoke",
+                &certificate_id,
+                &format!(
+                    "待验证：添加以下验证信息后重新执行吊销 {}",
+                    auths.join("; ")
+                ),
+            )]
+        }
+        Ok(RevokeStatus::Revoked) => {
+            info!(
+                "Revoked SSL certificate {} for domain {}",
+                certificate_id, domain_name
+            );
+            store.add_revoked(&certificate_id)?;
+            vec![DeployResult::success(
+                &domain_name,
+                "revoke",
+                &certificate_id,
+                "已吊销",
+            )]
+        }
+        Err(e) => {
+            return Ok(vec![DeployResult::failure(
+                &domain_name,
+                "revoke",
+                &certificate_id,
+                &e.to_string(),
+            )]);
+        }
+    };
+
+    if replace {
+        let domain = domain.ok_or_else(|| {
+            AppError::Other(format!(
+                "no domain in domains file uses certificate {}, cannot replace it",
+                certificate_id
+            ))
+        })?;
+        results.extend(update_ssl_certificate(vec![domain], store, true).await?);
+    }
+    Ok(results)
+}
+
+/// 按域名或证书 id 找到待吊销的证书及其所在账号
+async fn resolve_revoke_target(
+    domains: &[Domain],
+    store: &Store,
+    target: &str,
+) -> crate::Result<(Option<Domain>, String, CloudProvider)> {
+    if let Some(domain) = domains.iter().find(|domain| domain.name == target) {
+        let certificate_id = store.current_certificate_id(&domain.name)?.ok_or_else(|| {
+            AppError::Other(format!(
+                "no certificate recorded for domain {}, please specify the certificate id",
+                domain.name
+            ))
+        })?;
+        return Ok((
+            Some(domain.clone()),
+            certificate_id,
+            domain.ssl_provider.clone(),
+        ));
+    }
+
+    for domain in domains {
+        if store.current_certificate_id(&domain.name)?.as_deref() == Some(target) {
+            return Ok((
+                Some(domain.clone()),
+                target.to_string(),
+                domain.ssl_provider.clone(),
+            ));
+        }
+    }
+
+    // 不是本工具记录的证书时，在各 ssl_provider 账号中查找
+    let mut providers: Vec<&CloudProvider> = Vec::new();
+    for domain in domains {
+        if !providers.contains(&&domain.ssl_provider) {
+            providers.push(&domain.ssl_provider);
+        }
+    }
+    // 某个账号查询失败时继续在其他账号中查找
+    for provider in providers {
+        let certificates = match crate::ssl_client(provider) {
+            Ok(ssl_client) => ssl_client.list().await,
+            Err(e) => Err(e),
+        };
+        let certificates = match certificates {
+            Ok(certificates) => certificates,
+            Err(e) => {
+                warn!(
+                    "Failed to list certificates for account {}: {}",
+                    provider.account(),
+                    e
+                );
+                continue;
+            }
+        };
+        let Some(certificate) = certificates
+            .into_iter()
+            .find(|certificate| certificate.certificate_id == target)
+        else {
+            continue;
+        };
+        let domain = domains.iter().find(|domain| {
+            &domain.ssl_provider == provider
+                && domain
+                    .names()
+                    .iter()
+                    .all(|name| name_covered(&certificate.domains, name))
+        });
+        return Ok((domain.cloned(), target.to_string(), provider.clone()));
+    }
+
+    Err(AppError::Other(format!(
+        "certificate or domain not found: {}",
+        target
+    )))
+}
+
+/// 扫描 CDN 账号中的全部加速域名，按 DNS 账号托管的根域名确定 `original_name`，
+/// 生成以 profile 引用凭证的域名配置。`cdn_profiles` / `dns_profiles` 为空时使用服务商支持 CDN / DNS 的全部 profile，
+/// `ssl_profile` 为空时使用 CDN 账号签发证书，CDN 账号不能签发证书时需要指定
+pub async fn discover_domains(
+    profiles: &Profiles,
+    cdn_profiles: &[String],
+    dns_profiles: &[String],
+    ssl_profile: Option<&str>,
+) -> crate::Result<Vec<Value>> {
+    let cdn_profiles = if cdn_profiles.is_empty() {
+        profiles.names_for(CDN_PROVIDERS)
+    } else {
+        cdn_profiles.to_vec()
+    };
+    let dns_profiles = if dns_profiles.is_empty() {
+        profiles.names_for(DNS_PROVIDERS)
+    } else {
+        dns_profiles.to_vec()
+    };
+    // 扫描前检查全部 profile，避免查询到一半才报错
+    if let Some(ssl_profile) = ssl_profile {
+        profiles.get_for(ssl_profile, "ssl_provider", SSL_PROVIDERS)?;
+    }
+    let mut cdn_providers = Vec::with_capacity(cdn_profiles.len());
+    for name in &cdn_profiles {
+        let provider = profiles.get_for(name, "cdn_provider", CDN_PROVIDERS)?;
+        let ssl_profile = match ssl_profile {
+            Some(ssl_profile) => ssl_profile,
+            None if SSL_PROVIDERS.contains(&provider.name.as_str()) => name.as_str(),
+            None => {
+                return Err(AppError::ConfigError(format!(
+                    "profile {}: {} cannot issue certificates, use --ssl to choose the ssl_provider profile",
+                    name, provider.name
+                )));
+            }
+        };
+        cdn_providers.push((name, provider, ssl_profile));
+    }
+    let mut dns_providers = Vec::with_capacity(dns_profiles.len());
+    for name in &dns_profiles {
+        dns_providers.push((name, profiles.get_for(name, "dns_provider", DNS_PROVIDERS)?));
+    }
+
+    let mut zones = Vec::new();
+    let mut zone_profiles: HashMap<String, String> = HashMap::new();
+    for (name, provider) in dns_providers {
+        let dns_client = crate::dns_client(&provider)?;
+        match dns_client.list_zones().await {
+            Ok(account_zones) => {
+                for zone in account_zones {
+                    if !zone_profiles.contains_key(&zone) {
+                        zone_profiles.insert(zone.clone(), name.clone());
+                        zones.push(zone);
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to list DNS zones for profile {}: {}", name, e),
+        }
+    }
+
+    let mut discovered: Vec<Value> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    for (name, provider, ssl_profile) in cdn_providers {
+        let cdn_client = crate::cdn_client(&provider)?;
+        let configs = match cdn_client.list_https().await {
+            Ok(configs) => configs,
+            Err(e) => {
+                warn!("Failed to list CDN domains for profile {}: {}", name, e);
+                continue;
+            }
+        };
+        for config in configs {
+            if names.contains(&config.domain) {
+                continue;
+            }
+            let Some(zone) = find_zone(&config.domain, &zones) else {
+                warn!(
+                    "No DNS zone found for CDN domain {}, skipped",
+                    config.domain
+                );
+                continue;
+            };
+            info!("Discovered CDN domain {} in zone {}", config.domain, zone);
+            names.push(config.domain.clone());
+            discovered.push(json!({
+                "name": config.domain,
+                "original_name": zone,
+                "ssl_provider": { "profile": ssl_profile },
+                "dns_provider": { "profile": zone_profiles[zone] },
+                "cdn_provider": { "profile": name },
+            }));
+        }
+    }
+    discovered.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    Ok(discovered)
+}
+
+/// 合并到已有的域名配置文件，已存在的域名保持不变，返回新增的域名数量
+pub fn merge_domains(path: &str, discovered: Vec<Value>) -> crate::Result<usize> {
+    let mut domains: Vec<Value> = if Path::new(path).exists() {
+        serde_json::from_str(&std::fs::read_to_string(path)?)?
+    } else {
+        Vec::new()
+    };
+    let mut added = 0;
+    for domain in discovered {
+        if domains
+            .iter()
+            .any(|existing| existing["name"] == domain["name"])
+        {
+            continue;
+        }
+        domains.push(domain);
+        added += 1;
+    }
+    // 先写入同目录的临时文件再重命名，写入中断时不会破坏原配置文件
+    let temp_path = format!("{}.tmp", path);
+    std::fs::write(&temp_path, serde_json::to_string_pretty(&domains)?)?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(added)
 }
@@ -0,0 +1,353 @@
+use super::{Deploy, IssuedCertificate, VerifyOptions, verify_served_certificate};
+use crate::Result;
+use crate::cdn::{CDN, HttpsOptions};
+use crate::domain::CloudProvider;
+use crate::report::DeployResult;
+use crate::ssl::certificate_serial;
+use futures::future::join_all;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+pub struct CdnDeploy {
+    pub provider: CloudProvider,
+    pub verify: VerifyOptions,
+    pub https: HttpsOptions,
+}
+
+impl CdnDeploy {
+    pub fn new(provider: CloudProvider, verify: VerifyOptions, https: HttpsOptions) -> Self {
+        CdnDeploy {
+            provider,
+            verify,
+            https,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Deploy for CdnDeploy {
+    async fn deploy(&self, domain: &str, cert: &IssuedCertificate) -> Result<Vec<DeployResult>> {
+        // 证书不在当前账号下时先上传到该账号的 SSL 服务
+        let certificate_id = match &cert.source {
+            Some(source) if source.provider == self.provider => source.certificate_id.clone(),
+            _ => {
+                let ssl_client = crate::ssl_client(&self.provider)?;
+                let certificate_id = ssl_client
+                    .upload(&cert.bundle.full_chain(), &cert.bundle.key)
+                    .await?;
+                info!(
+                    "Uploaded SSL certificate for domain {} to {} account: {}",
+                    domain, self.provider.name, certificate_id
+                );
+                certificate_id
+            }
+        };
+
+        let cdn_client = crate::cdn_client(&self.provider)?;
+        // CDN 接口返回成功后仍需数分钟才能生效，且可能静默失败
+        let serial = if self.verify.enabled {
+            match certificate_serial(&cert.bundle.leaf) {
+                Ok(serial) => Some(serial),
+                Err(e) => {
+                    warn!(
+                        "Failed to read certificate serial for domain {}: {}",
+                        domain, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // 证书覆盖的每个非通配符域名都视为 CDN 加速域名
+        let tasks = cert
+            .names
+            .iter()
+            .filter(|name| !name.starts_with("*."))
+            .map(|host| {
+                self.deploy_host(
+                    cdn_client.clone(),
+                    domain,
+                    host,
+                    &certificate_id,
+                    serial.as_deref(),
+                )
+            });
+        Ok(join_all(tasks).await.into_iter().flatten().collect())
+    }
+}
+
+impl CdnDeploy {
+    /// 更新单个 CDN 域名的证书，更新或检查失败时恢复为原来的证书和 HTTPS 配置。
+    /// 证书中不是该账号 CDN 加速域名的 SAN 跳过，不计入部署结果
+    async fn deploy_host(
+        &self,
+        cdn_client: Arc<dyn CDN>,
+        domain: &str,
+        host: &str,
+        certificate_id: &str,
+        serial: Option<&str>,
+    ) -> Vec<DeployResult> {
+        let previous = match cdn_client.https_config(host).await {
+            Ok(None) if host != domain => {
+                info!(
+                    "Skip {} for domain {}: not a CDN domain of {} account",
+                    host, domain, self.provider.name
+                );
+                return Vec::new();
+            }
+            Ok(config) => config.filter(|config| config.certificate_id.is_some()),
+            Err(e) => {
+                warn!(
+                    "Failed to query current SSL certificate for domain {}: {}",
+                    host, e
+                );
+                None
+            }
+        };
+
+        let result = match cdn_client
+            .update_ssl(host, certificate_id, &self.https)
+            .await
+        {
+            Ok(result) => {
+                info!(
+                    "Update SSL certificate for domain {} success: {}",
+                    host, result
+                );
+                match serial {
+                    Some(serial) => verify_served_certificate(host, serial, &self.verify)
+                        .await
+                        .map(|_| result),
+                    None => Ok(result),
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        let error = match result {
+            Ok(result) => return vec![DeployResult::success(domain, "cdn", host, &result)],
+            Err(e) => e,
+        };
+        info!(
+            "Update SSL certificate for domain {} failed: {}",
+            host, error
+        );
+        let mut results = vec![DeployResult::failure(
+            domain,
+            "cdn",
+            host,
+            &error.to_string(),
+        )];
+
+        // 证书 id 相同时 HTTPS 选项也可能已被修改，同样需要恢复
+        if let Some(config) = previous
+            && let Some(previous) = config.certificate_id
+        {
+            let rollback = match cdn_client
+                .update_ssl(host, &previous, &config.options)
+                .await
+            {
+                Ok(_) => {
+                    info!(
+                        "Rolled back SSL certificate for domain {} to {}",
+                        host, previous
+                    );
+                    DeployResult::success(
+                        domain,
+                        "rollback",
+                        host,
+                        &format!("已恢复为证书 {}", previous),
+                    )
+                }
+                Err(e) => DeployResult::failure(
+                    domain,
+                    "rollback",
+                    host,
+                    &format!("恢复为证书 {} 失败: {}", previous, e),
+                ),
+            };
+            results.push(rollback);
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdn::HttpsConfig;
+    use std::sync::Mutex;
+
+    /// 部署 `fail_id` 时返回错误，记录每次更新的证书 id 和 HTTPS 选项
+    struct MockCdn {
+        /// CDN 加速域名，其他域名查询配置时返回 None
+        domains: Vec<String>,
+        current: Mutex<String>,
+        options: HttpsOptions,
+        fail_id: String,
+        updates: Mutex<Vec<(String, HttpsOptions)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl CDN for MockCdn {
+        async fn update_ssl(
+            &self,
+            _domain: &str,
+            cert_id: &str,
+            options: &HttpsOptions,
+        ) -> Result<String> {
+            self.updates
+                .lock()
+                .unwrap()
+                .push((cert_id.to_string(), options.clone()));
+            if cert_id == self.fail_id {
+                return Err("certificate rejected".into());
+            }
+            *self.current.lock().unwrap() = cert_id.to_string();
+            Ok("request-id".to_string())
+        }
+
+        async fn list_https(&self) -> Result<Vec<HttpsConfig>> {
+            Ok(Vec::new())
+        }
+
+        async fn https_config(&self, domain: &str) -> Result<Option<HttpsConfig>> {
+            if !self.domains.iter().any(|name| name == domain) {
+                return Ok(None);
+            }
+            Ok(Some(HttpsConfig {
+                domain: domain.to_string(),
+                https: true,
+                certificate_id: Some(self.current.lock().unwrap().clone()),
+                options: self.options.clone(),
+                ..Default::default()
+            }))
+        }
+    }
+
+    /// 更新失败时恢复为原来的证书和 HTTPS 选项，并输出 rollback 结果
+    #[tokio::test]
+    async fn test_deploy_host_rolls_back_on_failure() {
+        let requested = HttpsOptions {
+            http2: Some(true),
+            hsts: Some(true),
+            ..Default::default()
+        };
+        let previous = HttpsOptions {
+            http2: Some(false),
+            ocsp_stapling: Some(true),
+            force_redirect: Some(true),
+            hsts: Some(false),
+            hsts_max_age: Some(600),
+        };
+        let deploy = CdnDeploy::new(
+            CloudProvider::default(),
+            VerifyOptions::default(),
+            requested.clone(),
+        );
+        let cdn = Arc::new(MockCdn {
+            domains: vec!["www.example.com".to_string()],
+            current: Mutex::new("old".to_string()),
+            options: previous.clone(),
+            fail_id: "new".to_string(),
+            updates: Mutex::new(Vec::new()),
+        });
+        let results = deploy
+            .deploy_host(cdn.clone(), "example.com", "www.example.com", "new", None)
+            .await;
+
+        assert_eq!(results.len(), 2);
+        assert!(!results[0].success);
+        assert_eq!(results[0].target, "cdn");
+        assert!(results[1].success);
+        assert_eq!(results[1].target, "rollback");
+        assert_eq!(
+            *cdn.updates.lock().unwrap(),
+            vec![
+                ("new".to_string(), requested),
+                ("old".to_string(), previous)
+            ]
+        );
+        assert_eq!(*cdn.current.lock().unwrap(), "old");
+    }
+
+    /// 使用默认选项时更新失败同样回滚，是否开启检查不影响回滚
+    #[tokio::test]
+    async fn test_deploy_host_rolls_back_with_default_options() {
+        for verify in [
+            VerifyOptions::default(),
+            VerifyOptions {
+                enabled: false,
+                ..Default::default()
+            },
+        ] {
+            let serial = verify.enabled.then_some("01");
+            let deploy = CdnDeploy::new(CloudProvider::default(), verify, HttpsOptions::default());
+            let cdn = Arc::new(MockCdn {
+                domains: vec!["www.example.com".to_string()],
+                current: Mutex::new("old".to_string()),
+                options: HttpsOptions::default(),
+                fail_id: "new".to_string(),
+                updates: Mutex::new(Vec::new()),
+            });
+            let results = deploy
+                .deploy_host(
+                    cdn.clone(),
+                    "www.example.com",
+                    "www.example.com",
+                    "new",
+                    serial,
+                )
+                .await;
+
+            assert_eq!(results.len(), 2);
+            assert!(!results[0].success);
+            assert_eq!(results[1].target, "rollback");
+            assert!(results[1].success);
+            assert_eq!(*cdn.current.lock().unwrap(), "old");
+        }
+    }
+
+    /// 证书中的 SAN 不是 CDN 加速域名时跳过，不更新也不输出结果
+    #[tokio::test]
+    async fn test_deploy_host_skips_non_cdn_names() {
+        let deploy = CdnDeploy::new(
+            CloudProvider::default(),
+            VerifyOptions::default(),
+            HttpsOptions::default(),
+        );
+        let cdn = Arc::new(MockCdn {
+            domains: vec!["www.example.com".to_string()],
+            current: Mutex::new("old".to_string()),
+            options: HttpsOptions::default(),
+            fail_id: String::new(),
+            updates: Mutex::new(Vec::new()),
+        });
+        let results = deploy
+            .deploy_host(
+                cdn.clone(),
+                "www.example.com",
+                "api.example.com",
+                "new",
+                None,
+            )
+            .await;
+        assert!(results.is_empty());
+        assert!(cdn.updates.lock().unwrap().is_empty());
+
+        let results = deploy
+            .deploy_host(
+                cdn.clone(),
+                "www.example.com",
+                "www.example.com",
+                "new",
+                None,
+            )
+            .await;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].success);
+        assert_eq!(*cdn.current.lock().unwrap(), "new");
+    }
+}
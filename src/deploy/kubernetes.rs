@@ -0,0 +1,221 @@
+use super::{Deploy, IssuedCertificate};
+use crate::Result;
+use crate::error::AppError;
+use crate::report::DeployResult;
+use k8s_openapi::ByteString;
+use k8s_openapi::api::core::v1::Secret;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::Client;
+use kube::api::{Api, Patch, PatchParams};
+use kube::config::{Config, KubeConfigOptions, Kubeconfig};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tracing::info;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct KubernetesTarget {
+    pub namespace: String,
+    pub secret_name: String,
+    /// kubeconfig 文件路径，未设置时使用集群内（in-cluster）配置
+    pub kubeconfig: Option<String>,
+    pub context: Option<String>,
+}
+
+pub struct KubernetesDeploy {
+    pub target: KubernetesTarget,
+}
+
+impl KubernetesDeploy {
+    pub fn new(target: KubernetesTarget) -> Self {
+        KubernetesDeploy { target }
+    }
+
+    async fn client(&self) -> Result<Client> {
+        let config = match &self.target.kubeconfig {
+            Some(path) => {
+                let kubeconfig = Kubeconfig::read_from(path)
+                    .map_err(|e| AppError::ConfigError(e.to_string()))?;
+                let options = KubeConfigOptions {
+                    context: self.target.context.clone(),
+                    ..Default::default()
+                };
+                Config::from_custom_kubeconfig(kubeconfig, &options)
+                    .await
+                    .map_err(|e| AppError::ConfigError(e.to_string()))?
+            }
+            None => Config::incluster().map_err(|e| AppError::ConfigError(e.to_string()))?,
+        };
+        Ok(Client::try_from(config)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl Deploy for KubernetesDeploy {
+    async fn deploy(&self, domain: &str, cert: &IssuedCertificate) -> Result<Vec<DeployResult>> {
+        let host = format!("{}/{}", self.target.namespace, self.target.secret_name);
+        let client = match self.client().await {
+            Ok(client) => client,
+            Err(e) => {
+                return Ok(vec![DeployResult::failure(
+                    domain,
+                    "kubernetes",
+                    &host,
+                    &e.to_string(),
+                )]);
+            }
+        };
+
+        let secrets: Api<Secret> = Api::namespaced(client, &self.target.namespace);
+        let secret = Secret {
+            metadata: ObjectMeta {
+                name: Some(self.target.secret_name.clone()),
+                namespace: Some(self.target.namespace.clone()),
+                ..Default::default()
+            },
+            type_: Some("kubernetes.io/tls".to_string()),
+            data: Some(BTreeMap::from([
+                (
+                    "tls.crt".to_string(),
+                    ByteString(cert.bundle.full_chain().into_bytes()),
+                ),
+                (
+                    "tls.key".to_string(),
+                    ByteString(cert.bundle.key.as_bytes().to_vec()),
+                ),
+            ])),
+            ..Default::default()
+        };
+
+        // server-side apply 在 Secret 不存在时创建，存在时更新
+        let params = PatchParams::apply("csu").force();
+        match secrets
+            .patch(&self.target.secret_name, &params, &Patch::Apply(&secret))
+            .await
+        {
+            Ok(secret) => {
+                info!(
+                    "Deployed SSL certificate for domain {} to secret {}",
+                    domain, host
+                );
+                let version = secret.metadata.resource_version.unwrap_or_default();
+                Ok(vec![DeployResult::success(
+                    domain,
+                    "kubernetes",
+                    &host,
+                    &format!("resource version {}", version),
+                )])
+            }
+            Err(e) => {
+                info!(
+                    "Failed to deploy SSL certificate for domain {} to secret {}: {}",
+                    domain, host, e
+                );
+                Ok(vec![DeployResult::failure(
+                    domain,
+                    "kubernetes",
+                    &host,
+                    &e.to_string(),
+                )])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssl::CertBundle;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use zeroize::Zeroizing;
+
+    /// 读取一个 HTTP 请求，返回请求行和请求体
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> (String, String) {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            buffer.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buffer).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length || n == 0 {
+                    let request_line = head.lines().next().unwrap_or_default().to_string();
+                    return (request_line, body.to_string());
+                }
+            }
+        }
+    }
+
+    /// 本地模拟 API server，确认以 server-side apply 写入 TLS Secret
+    #[tokio::test]
+    async fn test_deploy_applies_tls_secret() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            let body = r#"{"apiVersion":"v1","kind":"Secret","metadata":{"name":"web-tls","namespace":"default","resourceVersion":"42"},"type":"kubernetes.io/tls"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            request
+        });
+
+        let kubeconfig = std::env::temp_dir().join(format!("csu-kubeconfig-{}.yaml", port));
+        std::fs::write(
+            &kubeconfig,
+            format!(
+                "apiVersion: v1\nkind: Config\nclusters:\n- name: mock\n  cluster:\n    server: http://127.0.0.1:{}\ncontexts:\n- name: mock\n  context:\n    cluster: mock\n    user: mock\ncurrent-context: mock\nusers:\n- name: mock\n  user:\n    token: test\n",
+                port
+            ),
+        )
+        .unwrap();
+
+        let deploy = KubernetesDeploy::new(KubernetesTarget {
+            namespace: "default".to_string(),
+            secret_name: "web-tls".to_string(),
+            kubeconfig: Some(kubeconfig.to_string_lossy().to_string()),
+            context: None,
+        });
+        let cert = IssuedCertificate {
+            source: None,
+            names: vec!["example.com".to_string()],
+            bundle: CertBundle {
+                leaf: "LEAF".to_string(),
+                intermediates: vec!["CHAIN".to_string()],
+                key: Zeroizing::new("KEY".to_string()),
+            },
+        };
+        let results = deploy.deploy("example.com", &cert).await.unwrap();
+        let _ = std::fs::remove_file(&kubeconfig);
+
+        assert!(results[0].success, "{}", results[0].message);
+        assert_eq!(results[0].host, "default/web-tls");
+        assert_eq!(results[0].message, "resource version 42");
+        let (request_line, body) = server.await.unwrap();
+        assert!(
+            request_line.starts_with("PATCH /api/v1/namespaces/default/secrets/web-tls?"),
+            "{}",
+            request_line
+        );
+        assert!(
+            request_line.contains("fieldManager=csu"),
+            "{}",
+            request_line
+        );
+        // tls.crt 为 LEAFCHAIN 的 base64
+        assert!(body.contains("TEVBRkNIQUlO"), "{}", body);
+        assert!(body.contains("kubernetes.io/tls"), "{}", body);
+    }
+}
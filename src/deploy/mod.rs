@@ -0,0 +1,81 @@
+mod cdn;
+mod kubernetes;
+mod ssh;
+mod verify;
+
+pub use cdn::CdnDeploy;
+pub use kubernetes::{KubernetesDeploy, KubernetesTarget};
+pub use ssh::{SshDeploy, SshTarget};
+pub use verify::{VerifyOptions, verify_served_certificate};
+
+use crate::Result;
+use crate::domain::CloudProvider;
+use crate::report::DeployResult;
+use crate::ssl::CertBundle;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DeployTarget {
+    Cdn(CloudProvider),
+    Ssh(SshTarget),
+    Kubernetes(KubernetesTarget),
+}
+
+impl DeployTarget {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeployTarget::Cdn(_) => "cdn",
+            DeployTarget::Ssh(_) => "ssh",
+            DeployTarget::Kubernetes(_) => "kubernetes",
+        }
+    }
+
+    /// 用于记录部署进度的唯一标识，会写入状态文件，CDN 账号使用脱敏后的 [`CloudProvider::account`]
+    pub fn key(&self) -> String {
+        match self {
+            DeployTarget::Cdn(provider) => format!("cdn:{}", provider.account()),
+            DeployTarget::Ssh(target) => format!("ssh:{}", target.hosts.join(",")),
+            DeployTarget::Kubernetes(target) => {
+                format!("kubernetes:{}/{}", target.namespace, target.secret_name)
+            }
+        }
+    }
+}
+
+/// 待部署的证书，由 ssl_provider 签发或从外部导入
+#[derive(Debug)]
+pub struct IssuedCertificate {
+    /// 签发证书的服务商，外部导入的证书为 None
+    pub source: Option<CertificateSource>,
+    /// 证书包含的全部域名
+    pub names: Vec<String>,
+    pub bundle: CertBundle,
+}
+
+#[derive(Debug, Clone)]
+pub struct CertificateSource {
+    pub certificate_id: String,
+    pub provider: CloudProvider,
+}
+
+#[async_trait::async_trait]
+pub trait Deploy: Send + Sync {
+    async fn deploy(&self, domain: &str, cert: &IssuedCertificate) -> Result<Vec<DeployResult>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cdn_key_masks_secret_id() {
+        let target = DeployTarget::Cdn(CloudProvider {
+            name: "tencent".to_string(),
+            secret_id: "AKIDEXAMPLE123456".to_string(),
+            secret_key: "secret".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(target.key(), "cdn:tencent:AKID****3456");
+    }
+}
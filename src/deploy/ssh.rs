@@ -0,0 +1,338 @@
+use super::{Deploy, IssuedCertificate};
+use crate::Result;
+use crate::error::AppError;
+use crate::report::DeployResult;
+use futures::future::join_all;
+use serde::Deserialize;
+use ssh2::{
+    CheckResult, ExtendedData, FileStat, KnownHostFileKind, OpenFlags, OpenType, RenameFlags,
+    Session,
+};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::info;
+
+const DEFAULT_PORT: u16 = 22;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 单次 SSH 操作（握手、上传、执行命令）的超时时间，避免无响应的主机一直占用线程
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SshTarget {
+    /// 主机列表，格式为 host、host:port 或 [IPv6]:port
+    pub hosts: Vec<String>,
+    pub user: String,
+    /// 私钥文件路径
+    pub private_key: String,
+    pub passphrase: Option<String>,
+    /// known_hosts 文件路径，默认为 `~/.ssh/known_hosts`，服务器公钥不在其中时拒绝连接
+    pub known_hosts: Option<String>,
+    pub cert_path: String,
+    pub key_path: String,
+    pub reload_command: Option<String>,
+}
+
+pub struct SshDeploy {
+    pub target: SshTarget,
+}
+
+impl SshDeploy {
+    pub fn new(target: SshTarget) -> Self {
+        SshDeploy { target }
+    }
+}
+
+#[async_trait::async_trait]
+impl Deploy for SshDeploy {
+    async fn deploy(&self, domain: &str, cert: &IssuedCertificate) -> Result<Vec<DeployResult>> {
+        let tasks = self.target.hosts.iter().map(|host| {
+            let target = self.target.clone();
+            let host = host.clone();
+            let full_chain = cert.bundle.full_chain();
+            let key = cert.bundle.key.clone();
+            tokio::task::spawn_blocking(move || {
+                let result = deploy_host(&target, &host, &full_chain, &key);
+                (host, result)
+            })
+        });
+
+        let mut results = Vec::with_capacity(self.target.hosts.len());
+        for task in join_all(tasks).await {
+            let (host, result) = task.map_err(|e| AppError::Other(e.to_string()))?;
+            match result {
+                Ok(message) => {
+                    info!("Deployed SSL certificate for domain {} to {}", domain, host);
+                    results.push(DeployResult::success(domain, "ssh", &host, &message));
+                }
+                Err(e) => {
+                    info!(
+                        "Failed to deploy SSL certificate for domain {} to {}: {}",
+                        domain, host, e
+                    );
+                    results.push(DeployResult::failure(domain, "ssh", &host, &e.to_string()));
+                }
+            }
+        }
+        Ok(results)
+    }
+}
+
+fn deploy_host(target: &SshTarget, host: &str, full_chain: &str, key: &str) -> Result<String> {
+    let (hostname, port) = split_host_port(host)?;
+    let known_hosts = known_hosts_path(target)?;
+
+    let tcp = connect(&hostname, port)?;
+    let mut session = Session::new()?;
+    session.set_timeout(SESSION_TIMEOUT.as_millis() as u32);
+    session.set_tcp_stream(tcp);
+    session.handshake()?;
+    // 私钥上传前必须确认服务器身份
+    verify_host_key(&session, &known_hosts, &hostname, port)?;
+
+    session.userauth_pubkey_file(
+        &target.user,
+        None,
+        Path::new(&target.private_key),
+        target.passphrase.as_deref(),
+    )?;
+
+    let sftp = session.sftp()?;
+    upload_file(&session, &sftp, &target.cert_path, full_chain, 0o644)?;
+    upload_file(&session, &sftp, &target.key_path, key, 0o600)?;
+
+    let Some(command) = &target.reload_command else {
+        return Ok("uploaded".to_string());
+    };
+
+    exec(&session, command)
+        .map_err(|e| AppError::SshError(format!("reload command failed: {}", e)))?;
+    Ok(format!("uploaded, reloaded: {}", command))
+}
+
+/// 依次尝试主机解析出的地址，每个地址的连接超时为 [`CONNECT_TIMEOUT`]
+fn connect(hostname: &str, port: u16) -> Result<TcpStream> {
+    let mut last_error = None;
+    for address in (hostname, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+            Ok(tcp) => return Ok(tcp),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(match last_error {
+        Some(e) => e.into(),
+        None => AppError::SshError(format!("no address found for {}", hostname)),
+    })
+}
+
+/// 执行命令，退出码不为 0 时返回包含输出的错误
+fn exec(session: &Session, command: &str) -> Result<()> {
+    let mut channel = session.channel_session()?;
+    // stderr 合并到 stdout 中读取，避免先读 stdout 时 stderr 写满导致命令阻塞
+    channel.handle_extended_data(ExtendedData::Merge)?;
+    channel.exec(command)?;
+    let mut output = String::new();
+    channel.read_to_string(&mut output)?;
+    channel.wait_close()?;
+    let exit_status = channel.exit_status()?;
+    if exit_status != 0 {
+        return Err(AppError::SshError(format!(
+            "exited with {}: {}",
+            exit_status,
+            output.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// 解析 host、host:port、[IPv6]:port 和不带端口的 IPv6 地址
+fn split_host_port(host: &str) -> Result<(String, u16)> {
+    let invalid = || AppError::ConfigError(format!("invalid ssh host: {}", host));
+    if let Some(rest) = host.strip_prefix('[') {
+        let (hostname, rest) = rest.split_once(']').ok_or_else(invalid)?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().map_err(|_| invalid())?,
+            None if rest.is_empty() => DEFAULT_PORT,
+            None => return Err(invalid()),
+        };
+        return Ok((hostname.to_string(), port));
+    }
+    match host.split_once(':') {
+        // 含多个冒号的是不带端口的 IPv6 地址
+        Some((_, rest)) if rest.contains(':') => Ok((host.to_string(), DEFAULT_PORT)),
+        Some((hostname, port)) => Ok((hostname.to_string(), port.parse().map_err(|_| invalid())?)),
+        None => Ok((host.to_string(), DEFAULT_PORT)),
+    }
+}
+
+fn known_hosts_path(target: &SshTarget) -> Result<PathBuf> {
+    match &target.known_hosts {
+        Some(known_hosts) => Ok(PathBuf::from(known_hosts)),
+        None => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".ssh").join("known_hosts"))
+            .ok_or_else(|| {
+                AppError::ConfigError("known_hosts is not set and HOME is unknown".to_string())
+            }),
+    }
+}
+
+fn verify_host_key(session: &Session, known_hosts: &Path, hostname: &str, port: u16) -> Result<()> {
+    let mut hosts = session.known_hosts()?;
+    hosts
+        .read_file(known_hosts, KnownHostFileKind::OpenSSH)
+        .map_err(|e| {
+            AppError::SshError(format!(
+                "failed to read known_hosts {}: {}",
+                known_hosts.display(),
+                e
+            ))
+        })?;
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| AppError::SshError("server did not send a host key".to_string()))?;
+    match hosts.check_port(hostname, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(AppError::SshError(format!(
+            "host key mismatch for {}",
+            hostname
+        ))),
+        CheckResult::NotFound => Err(AppError::SshError(format!(
+            "host {} not found in {}",
+            hostname,
+            known_hosts.display()
+        ))),
+        CheckResult::Failure => Err(AppError::SshError(format!(
+            "failed to check host key for {}",
+            hostname
+        ))),
+    }
+}
+
+/// 先写入同目录下的临时文件并设置权限，再重命名为目标文件，
+/// 避免私钥以原文件的权限写入或被读取到写了一半的内容
+fn upload_file(
+    session: &Session,
+    sftp: &ssh2::Sftp,
+    path: &str,
+    content: &str,
+    mode: i32,
+) -> Result<()> {
+    let path = Path::new(path);
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| AppError::ConfigError(format!("invalid remote path: {}", path.display())))?;
+    let temp = path.with_file_name(format!(".{}.csu-tmp", file_name.to_string_lossy()));
+
+    let mut file = sftp.open_mode(
+        &temp,
+        OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE,
+        mode,
+        OpenType::File,
+    )?;
+    // 临时文件已存在时 open_mode 不会修改权限，写入内容前先设置
+    file.setstat(FileStat {
+        size: None,
+        uid: None,
+        gid: None,
+        perm: Some(mode as u32),
+        atime: None,
+        mtime: None,
+    })?;
+    file.write_all(content.as_bytes())?;
+    drop(file);
+
+    // 不能先删除目标文件再重命名，中间会有一段时间目标文件不存在
+    let flags = RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE;
+    let Err(rename_error) = sftp.rename(&temp, path, Some(flags)) else {
+        return Ok(());
+    };
+    // SFTP v3 服务器（如 OpenSSH）忽略覆盖标志，目标文件存在时重命名失败，
+    // 改为在服务器上执行 mv，同一文件系统内由 rename(2) 原子替换
+    let command = format!(
+        "mv -f -- {} {}",
+        shell_quote(&temp.to_string_lossy()),
+        shell_quote(&path.to_string_lossy())
+    );
+    if let Err(mv_error) = exec(session, &command) {
+        let _ = sftp.unlink(&temp);
+        return Err(AppError::SshError(format!(
+            "server does not support atomic overwrite of {}: sftp rename failed: {}; mv failed: {}",
+            path.display(),
+            rename_error,
+            mv_error
+        )));
+    }
+    Ok(())
+}
+
+/// 用单引号包裹参数，参数中的单引号替换为 `'\''`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssl::CertBundle;
+    use std::net::TcpListener;
+    use zeroize::Zeroizing;
+
+    #[test]
+    fn test_split_host_port() {
+        let cases = [
+            ("10.0.0.1", ("10.0.0.1", 22)),
+            ("example.com:2222", ("example.com", 2222)),
+            ("[2001:db8::1]:2222", ("2001:db8::1", 2222)),
+            ("[2001:db8::1]", ("2001:db8::1", 22)),
+            ("2001:db8::1", ("2001:db8::1", 22)),
+        ];
+        for (host, (hostname, port)) in cases {
+            assert_eq!(split_host_port(host).unwrap(), (hostname.to_string(), port));
+        }
+        assert!(split_host_port("example.com:ssh").is_err());
+        assert!(split_host_port("[2001:db8::1]2222").is_err());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/etc/ssl/cert.pem"), "'/etc/ssl/cert.pem'");
+        assert_eq!(shell_quote("/tmp/it's.pem"), r"'/tmp/it'\''s.pem'");
+    }
+
+    /// 本地监听一个不说 SSH 协议的端口代替 sshd，握手失败时每台主机单独记为失败
+    #[tokio::test]
+    async fn test_deploy_reports_failed_host() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+            }
+        });
+
+        let deploy = SshDeploy::new(SshTarget {
+            hosts: vec![address.clone()],
+            user: "root".to_string(),
+            private_key: "/nonexistent/id_ed25519".to_string(),
+            passphrase: None,
+            known_hosts: Some("/nonexistent/known_hosts".to_string()),
+            cert_path: "/tmp/cert.pem".to_string(),
+            key_path: "/tmp/cert.key".to_string(),
+            reload_command: None,
+        });
+        let cert = IssuedCertificate {
+            source: None,
+            names: vec!["example.com".to_string()],
+            bundle: CertBundle {
+                leaf: String::new(),
+                intermediates: Vec::new(),
+                key: Zeroizing::new(String::new()),
+            },
+        };
+        let results = deploy.deploy("example.com", &cert).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].host, address);
+        assert!(!results[0].success);
+    }
+}
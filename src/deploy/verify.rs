@@ -0,0 +1,96 @@
+use crate::Result;
+use crate::error::AppError;
+use crate::ssl::check_ssl_certificate_at;
+use serde::Deserialize;
+use tokio::time::{Duration, Instant, sleep};
+use tracing::{debug, info};
+
+/// 部署到 CDN 后确认域名实际返回新证书的配置，默认开启，每个域名最多等待 `timeout_secs`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct VerifyOptions {
+    pub enabled: bool,
+    /// 逐个检查的 CDN 边缘节点 IP，为空时按 DNS 解析结果检查
+    pub edge_ips: Vec<String>,
+    pub timeout_secs: u64,
+    pub interval_secs: u64,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            enabled: true,
+            edge_ips: Vec::new(),
+            timeout_secs: 900,
+            interval_secs: 30,
+        }
+    }
+}
+
+/// 反复检查 `host` 返回的证书序列号，直到全部地址都与 `serial` 一致或超时
+pub async fn verify_served_certificate(
+    host: &str,
+    serial: &str,
+    options: &VerifyOptions,
+) -> Result<()> {
+    let addresses: Vec<Option<String>> = if options.edge_ips.is_empty() {
+        vec![None]
+    } else {
+        options.edge_ips.iter().cloned().map(Some).collect()
+    };
+    let deadline = Instant::now() + Duration::from_secs(options.timeout_secs);
+    let mut pending = addresses;
+
+    loop {
+        let mut still_pending = Vec::new();
+        for address in pending {
+            let check_host = host.to_string();
+            let check_address = address.clone();
+            let served = tokio::task::spawn_blocking(move || {
+                check_ssl_certificate_at(&check_host, check_address.as_deref())
+            })
+            .await;
+            match served {
+                Ok(Ok(info)) if info.serial == serial => {}
+                Ok(Ok(info)) => {
+                    debug!(
+                        "Domain {} at {} still serves certificate {}",
+                        host,
+                        address.as_deref().unwrap_or("dns"),
+                        info.serial
+                    );
+                    still_pending.push(address);
+                }
+                Ok(Err(e)) => {
+                    debug!("Check SSL certificate for domain {} failed: {}", host, e);
+                    still_pending.push(address);
+                }
+                Err(e) => {
+                    debug!(
+                        "Check SSL certificate task for domain {} failed: {}",
+                        host, e
+                    );
+                    still_pending.push(address);
+                }
+            }
+        }
+
+        if still_pending.is_empty() {
+            info!("Domain {} serves the new SSL certificate {}", host, serial);
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            let addresses: Vec<&str> = still_pending
+                .iter()
+                .map(|address| address.as_deref().unwrap_or(host))
+                .collect();
+            return Err(AppError::Other(format!(
+                "new certificate is not served after {}s: {}",
+                options.timeout_secs,
+                addresses.join(",")
+            )));
+        }
+        pending = still_pending;
+        sleep(Duration::from_secs(options.interval_secs)).await;
+    }
+}
@@ -0,0 +1,196 @@
+use super::DNS;
+use crate::Result;
+use crate::domain::CloudProvider;
+use crate::error::AppError;
+use tokio::process::Command;
+use tracing::debug;
+
+/// 通过用户配置的命令管理 DNS 记录，命令由 `sh -c` 执行。
+///
+/// `options` 中的命令：`add`、`delete` 必填，`modify`、`record_id`、`list_zones` 可选。
+/// 记录信息通过 `CSU_DNS_*` 环境变量传入，`add`、`modify`、`record_id` 从标准输出的最后一行读取记录 id，
+/// `list_zones` 每行输出一个根域名。`delete` 只收到记录 id，因此 `add` 必须输出 id
+pub struct HookDNS {
+    add: String,
+    modify: Option<String>,
+    delete: String,
+    record_id: Option<String>,
+    list_zones: Option<String>,
+    secret_id: String,
+    secret_key: String,
+}
+
+impl HookDNS {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        let required = |name: &str| {
+            provider.options.get(name).cloned().ok_or_else(|| {
+                AppError::ConfigError(format!("hook dns provider requires option: {}", name))
+            })
+        };
+        Ok(HookDNS {
+            add: required("add")?,
+            modify: provider.options.get("modify").cloned(),
+            delete: required("delete")?,
+            record_id: provider.options.get("record_id").cloned(),
+            list_zones: provider.options.get("list_zones").cloned(),
+            secret_id: provider.secret_id.clone(),
+            secret_key: provider.secret_key.clone(),
+        })
+    }
+
+    async fn run(&self, action: &str, command: &str, env: &[(&str, String)]) -> Result<String> {
+        debug!("Run dns hook {}: {}", action, command);
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("CSU_DNS_ACTION", action)
+            .env("CSU_DNS_SECRET_ID", &self.secret_id)
+            .env("CSU_DNS_SECRET_KEY", &self.secret_key)
+            .envs(env.iter().map(|(name, value)| (*name, value)))
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(AppError::CloudError(format!(
+                "dns hook {} exited with {}: {}",
+                action,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn record_env(domain: &str, sub_domain: &str) -> Vec<(&'static str, String)> {
+    let fqdn = if sub_domain.is_empty() || sub_domain == "@" {
+        domain.to_string()
+    } else {
+        format!("{}.{}", sub_domain, domain)
+    };
+    vec![
+        ("CSU_DNS_ZONE", domain.to_string()),
+        ("CSU_DNS_SUB_DOMAIN", sub_domain.to_string()),
+        ("CSU_DNS_FQDN", fqdn),
+    ]
+}
+
+/// 取标准输出最后一个非空行作为记录 id，没有输出时为 None
+fn parse_record_id(stdout: &str) -> Result<Option<u64>> {
+    match stdout.lines().map(str::trim).rfind(|line| !line.is_empty()) {
+        Some(line) => line
+            .parse()
+            .map(Some)
+            .map_err(|_| AppError::CloudError(format!("invalid dns hook record id: {}", line))),
+        None => Ok(None),
+    }
+}
+
+#[async_trait::async_trait]
+impl DNS for HookDNS {
+    async fn add_record(&self, record: &str, domain: &str, sub_domain: &str) -> Result<u64> {
+        let mut env = record_env(domain, sub_domain);
+        env.push(("CSU_DNS_RECORD_VALUE", record.to_string()));
+        parse_record_id(&self.run("add", &self.add, &env).await?)?.ok_or_else(|| {
+            AppError::CloudError("dns hook add must print the record id used by delete".to_string())
+        })
+    }
+
+    async fn modify_record(
+        &self,
+        record: &str,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<u64> {
+        let Some(modify) = &self.modify else {
+            // 没有 modify 命令时先删除旧记录再添加
+            self.delete_record(record_id, domain, sub_domain).await?;
+            return self.add_record(record, domain, sub_domain).await;
+        };
+        let mut env = record_env(domain, sub_domain);
+        env.push(("CSU_DNS_RECORD_VALUE", record.to_string()));
+        env.push(("CSU_DNS_RECORD_ID", record_id.to_string()));
+        // 没有输出新 id 时沿用原记录 id
+        Ok(parse_record_id(&self.run("modify", modify, &env).await?)?.unwrap_or(record_id))
+    }
+
+    async fn delete_record(
+        &self,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<String> {
+        let mut env = record_env(domain, sub_domain);
+        env.push(("CSU_DNS_RECORD_ID", record_id.to_string()));
+        let stdout = self.run("delete", &self.delete, &env).await?;
+        Ok(stdout.trim().to_string())
+    }
+
+    async fn record_id(&self, domain: &str, sub_domain: &str) -> Result<u64> {
+        let Some(command) = &self.record_id else {
+            return Err(AppError::CloudError(
+                "dns hook record_id command not configured".to_string(),
+            ));
+        };
+        let stdout = self
+            .run("record_id", command, &record_env(domain, sub_domain))
+            .await?;
+        parse_record_id(&stdout)?.ok_or_else(|| AppError::CloudError("no dns record".to_string()))
+    }
+
+    async fn list_zones(&self) -> Result<Vec<String>> {
+        let Some(command) = &self.list_zones else {
+            return Ok(Vec::new());
+        };
+        let stdout = self.run("list_zones", command, &[]).await?;
+        Ok(stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.trim_end_matches('.').to_string())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_hook_add_record() {
+        let provider = CloudProvider {
+            name: "hook".to_string(),
+            options: BTreeMap::from([
+                (
+                    "add".to_string(),
+                    r#"test "$CSU_DNS_FQDN" = "_dnsauth.www.example.com" && echo 42"#.to_string(),
+                ),
+                (
+                    "delete".to_string(),
+                    r#"test "$CSU_DNS_FQDN.$CSU_DNS_RECORD_ID" = "_dnsauth.www.example.com.42" && echo deleted"#.to_string(),
+                ),
+            ]),
+            ..Default::default()
+        };
+        let dns = HookDNS::new(&provider).unwrap();
+        let record_id = dns
+            .add_record("value", "example.com", "_dnsauth.www")
+            .await
+            .unwrap();
+        assert_eq!(record_id, 42);
+        assert_eq!(
+            dns.delete_record(record_id, "example.com", "_dnsauth.www")
+                .await
+                .unwrap(),
+            "deleted"
+        );
+        assert!(dns.record_id("example.com", "_dnsauth.www").await.is_err());
+        // 没有输出 id 时无法删除记录，添加视为失败
+        assert!(
+            dns.add_record("value", "example.com", "other")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use super::DNS;
+use super::records::{self, TxtRecordSet, record_name};
+use crate::Result;
+use crate::domain::{CloudProvider, Endpoint};
+use crate::error::AppError;
+use crate::huawei::HuaweiClient;
+use reqwest::Method;
+use serde_json::{Value, json};
+use tracing::info;
+
+const DEFAULT_ENDPOINT: &str = "https://dns.myhuaweicloud.com";
+const DEFAULT_TTL: u64 = 300;
+const LIST_ZONES_LIMIT: usize = 500;
+
+/// 华为云云解析 DNS 的公网域名，`options.dns_endpoint` 可覆盖接口地址
+pub struct HuaweiDNS {
+    client: HuaweiClient,
+    endpoint: Endpoint,
+}
+
+impl HuaweiDNS {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        Ok(HuaweiDNS {
+            client: HuaweiClient::new(provider),
+            endpoint: provider.endpoint("dns_endpoint", DEFAULT_ENDPOINT)?,
+        })
+    }
+
+    async fn zone_id(&self, domain: &str) -> Result<String> {
+        let mut url = self.endpoint.url("/v2/zones")?;
+        url.query_pairs_mut()
+            .append_pair("type", "public")
+            .append_pair("name", domain);
+        let response = self.client.request(Method::GET, url, None).await?;
+        response["zones"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .find(|zone| {
+                zone["name"]
+                    .as_str()
+                    .is_some_and(|name| name.trim_end_matches('.') == domain)
+            })
+            .and_then(|zone| zone["id"].as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                AppError::CloudError(format!("huawei cloud dns zone not found: {}", domain))
+            })
+    }
+
+    /// 记录名下当前的记录集及其 id
+    async fn txt_record_set(
+        &self,
+        zone_id: &str,
+        name: &str,
+    ) -> Result<Option<(String, TxtRecordSet)>> {
+        let mut url = self
+            .endpoint
+            .url(&format!("/v2/zones/{}/recordsets", zone_id))?;
+        url.query_pairs_mut()
+            .append_pair("type", "TXT")
+            .append_pair("name", name)
+            .append_pair("search_mode", "equal");
+        let response = self.client.request(Method::GET, url, None).await?;
+        let record_set = response["recordsets"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .find(|record_set| {
+                record_set["name"].as_str() == Some(name) && record_set["type"] == "TXT"
+            });
+        Ok(record_set.and_then(|record_set| {
+            let id = record_set["id"].as_str()?.to_string();
+            Some((
+                id,
+                TxtRecordSet {
+                    name: name.to_string(),
+                    ttl: record_set["ttl"].as_u64().unwrap_or(DEFAULT_TTL),
+                    values: record_set["records"]
+                        .as_array()
+                        .map(Vec::as_slice)
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(|value| value.trim_matches('"').to_string())
+                        .collect(),
+                },
+            ))
+        }))
+    }
+
+    /// 记录集不存在（`id` 为 None）时创建，否则整体替换其中的值
+    async fn save(&self, zone_id: &str, id: Option<&str>, record_set: &TxtRecordSet) -> Result<()> {
+        let records: Vec<String> = record_set
+            .values
+            .iter()
+            .map(|value| format!("\"{}\"", value))
+            .collect();
+        let body = json!({
+            "name": record_set.name,
+            "type": "TXT",
+            "ttl": record_set.ttl,
+            "records": records,
+        });
+        match id {
+            Some(id) => {
+                let url = self
+                    .endpoint
+                    .url(&format!("/v2/zones/{}/recordsets/{}", zone_id, id))?;
+                self.client.request(Method::PUT, url, Some(&body)).await?;
+            }
+            None => {
+                let url = self
+                    .endpoint
+                    .url(&format!("/v2/zones/{}/recordsets", zone_id))?;
+                self.client.request(Method::POST, url, Some(&body)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 记录名下当前的记录集，不存在时为空记录集
+    async fn record_set(
+        &self,
+        zone_id: &str,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<(Option<String>, TxtRecordSet)> {
+        let name = record_name(domain, sub_domain);
+        Ok(match self.txt_record_set(zone_id, &name).await? {
+            Some((id, record_set)) => (Some(id), record_set),
+            None => (None, TxtRecordSet::new(&name, DEFAULT_TTL)),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl DNS for HuaweiDNS {
+    async fn add_record(&self, record: &str, domain: &str, sub_domain: &str) -> Result<u64> {
+        let zone_id = self.zone_id(domain).await?;
+        let (id, mut record_set) = self.record_set(&zone_id, domain, sub_domain).await?;
+        let record_id = record_set.add(record)?;
+        self.save(&zone_id, id.as_deref(), &record_set).await?;
+        info!("Added huawei cloud TXT record {}", record_set.name);
+        Ok(record_id)
+    }
+
+    async fn modify_record(
+        &self,
+        record: &str,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<u64> {
+        let zone_id = self.zone_id(domain).await?;
+        let (id, mut record_set) = self.record_set(&zone_id, domain, sub_domain).await?;
+        let record_id = record_set.replace(record_id, record)?;
+        self.save(&zone_id, id.as_deref(), &record_set).await?;
+        Ok(record_id)
+    }
+
+    /// 移除最后一个值时删除整个记录集，记录已不存在时视为删除成功
+    async fn delete_record(
+        &self,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<String> {
+        let zone_id = self.zone_id(domain).await?;
+        let name = record_name(domain, sub_domain);
+        let Some((id, mut record_set)) = self.txt_record_set(&zone_id, &name).await? else {
+            return Ok(String::new());
+        };
+        if !record_set.remove(record_id)? {
+            return Ok(String::new());
+        }
+        if record_set.values.is_empty() {
+            let url = self
+                .endpoint
+                .url(&format!("/v2/zones/{}/recordsets/{}", zone_id, id))?;
+            self.client.request(Method::DELETE, url, None).await?;
+        } else {
+            self.save(&zone_id, Some(&id), &record_set).await?;
+        }
+        Ok(id)
+    }
+
+    async fn record_id(&self, domain: &str, sub_domain: &str) -> Result<u64> {
+        let zone_id = self.zone_id(domain).await?;
+        let name = record_name(domain, sub_domain);
+        match self
+            .txt_record_set(&zone_id, &name)
+            .await?
+            .and_then(|(_, record_set)| record_set.values.into_iter().next())
+        {
+            Some(value) => records::record_id(&name, &value),
+            None => Err(AppError::CloudError("no dns record".to_string())),
+        }
+    }
+
+    async fn list_zones(&self) -> Result<Vec<String>> {
+        let mut zones = Vec::new();
+        loop {
+            let mut url = self.endpoint.url("/v2/zones")?;
+            url.query_pairs_mut()
+                .append_pair("type", "public")
+                .append_pair("limit", &LIST_ZONES_LIMIT.to_string())
+                .append_pair("offset", &zones.len().to_string());
+            let response = self.client.request(Method::GET, url, None).await?;
+            let page: Vec<String> = response["zones"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|zone| zone["name"].as_str())
+                .map(|name| name.trim_end_matches('.').to_string())
+                .collect();
+            let total = response["metadata"]["total_count"].as_u64().unwrap_or(0) as usize;
+            let done = page.len() < LIST_ZONES_LIMIT;
+            zones.extend(page);
+            if done || zones.len() >= total {
+                break;
+            }
+        }
+        Ok(zones)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 按顺序返回 `responses`，返回收到的请求
+    async fn serve(listener: TcpListener, responses: Vec<Value>) -> Vec<String> {
+        let mut requests = Vec::new();
+        for body in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut chunk).await.unwrap();
+                buffer.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&buffer).to_string();
+                if let Some((head, content)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if content.len() >= length || n == 0 {
+                        break;
+                    }
+                }
+            }
+            requests.push(String::from_utf8_lossy(&buffer).to_string());
+            let body = body.to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        requests
+    }
+
+    /// 修改时只替换记录集中对应的值，保留其他值和原有 TTL
+    #[tokio::test]
+    async fn test_modify_record_keeps_other_values_and_ttl() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(serve(
+            listener,
+            vec![
+                json!({ "zones": [{ "id": "z1", "name": "example.com." }] }),
+                json!({ "recordsets": [{
+                    "id": "r1",
+                    "name": "_dnsauth.example.com.",
+                    "type": "TXT",
+                    "ttl": 600,
+                    "records": ["\"other\"", "\"old\""],
+                }] }),
+                json!({ "id": "r1" }),
+            ],
+        ));
+
+        let provider = CloudProvider {
+            name: "huawei".to_string(),
+            secret_id: "HPUA".to_string(),
+            secret_key: "SECRET".to_string(),
+            options: BTreeMap::from([("dns_endpoint".to_string(), endpoint)]),
+            ..Default::default()
+        };
+        let dns = HuaweiDNS::new(&provider).unwrap();
+        let name = "_dnsauth.example.com.";
+        let old_id = records::record_id(name, "old").unwrap();
+        let new_id = dns
+            .modify_record("new", old_id, "example.com", "_dnsauth")
+            .await
+            .unwrap();
+        assert_eq!(new_id, records::record_id(name, "new").unwrap());
+
+        let requests = handle.await.unwrap();
+        let (head, body) = requests[2].split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("PUT /v2/zones/z1/recordsets/r1 "));
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["ttl"], 600);
+        assert_eq!(body["records"], json!(["\"other\"", "\"new\""]));
+    }
+}
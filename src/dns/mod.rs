@@ -1,5 +1,14 @@
+mod hook;
+mod huawei;
+mod records;
+mod rfc2136;
+mod route53;
 mod tencent;
 
+pub use hook::HookDNS;
+pub use huawei::HuaweiDNS;
+pub use rfc2136::Rfc2136DNS;
+pub use route53::Route53DNS;
 pub use tencent::TencentDNS;
 
 use crate::Result;
@@ -14,6 +23,10 @@ pub trait DNS: Send + Sync {
         domain: &str,
         sub_domain: &str,
     ) -> Result<u64>;
-    async fn delete_record(&self, record_id: u64, domain: &str) -> Result<String>;
+    /// `sub_domain` 为添加记录时的主机记录，没有记录 id 的服务据此查询要删除的记录
+    async fn delete_record(&self, record_id: u64, domain: &str, sub_domain: &str)
+    -> Result<String>;
     async fn record_id(&self, domain: &str, sub_domain: &str) -> Result<u64>;
+    /// 列出账号中托管的全部根域名
+    async fn list_zones(&self) -> Result<Vec<String>>;
 }
@@ -0,0 +1,114 @@
+use crate::Result;
+use openssl::hash::{MessageDigest, hash};
+
+/// 按完整记录名和记录值生成 id，同一条记录每次得到相同的 id。
+/// RFC 2136、Route 53、华为云按记录名管理 TXT 记录，没有单条记录的 id，修改和删除时查询当前的值找到对应的记录
+pub(crate) fn record_id(name: &str, value: &str) -> Result<u64> {
+    let key = format!("{} {}", name.trim_end_matches('.').to_lowercase(), value);
+    let digest = hash(MessageDigest::sha256(), key.as_bytes())?;
+    Ok(u64::from_be_bytes(digest[..8].try_into().unwrap()))
+}
+
+/// 完整记录名，以 `.` 结尾
+pub(crate) fn record_name(domain: &str, sub_domain: &str) -> String {
+    if sub_domain.is_empty() || sub_domain == "@" {
+        format!("{}.", domain)
+    } else {
+        format!("{}.{}.", sub_domain, domain)
+    }
+}
+
+/// 在记录名下的 TXT 值中找到 id 为 `id` 的值
+pub(crate) fn find_value(name: &str, values: &[String], id: u64) -> Result<Option<usize>> {
+    for (index, value) in values.iter().enumerate() {
+        if record_id(name, value)? == id {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+/// 同一记录名下的全部 TXT 值。Route 53 和华为云中同名 TXT 记录是一个记录集，只能整体提交，
+/// 因此只改动本工具添加的值，其他值和 TTL 保持不变
+pub(crate) struct TxtRecordSet {
+    pub name: String,
+    pub ttl: u64,
+    pub values: Vec<String>,
+}
+
+impl TxtRecordSet {
+    pub fn new(name: &str, ttl: u64) -> Self {
+        TxtRecordSet {
+            name: name.to_string(),
+            ttl,
+            values: Vec::new(),
+        }
+    }
+
+    /// 添加值，已存在时不重复添加，返回记录 id
+    pub fn add(&mut self, value: &str) -> Result<u64> {
+        if !self.values.iter().any(|current| current == value) {
+            self.values.push(value.to_string());
+        }
+        record_id(&self.name, value)
+    }
+
+    /// 把 id 为 `id` 的值替换为新值，找不到时追加，返回新值的 id
+    pub fn replace(&mut self, id: u64, value: &str) -> Result<u64> {
+        match find_value(&self.name, &self.values, id)? {
+            // 新值已经存在时只移除旧值，记录集中不能有重复的值
+            Some(index) if self.values.iter().any(|current| current == value) => {
+                if self.values[index] != value {
+                    self.values.remove(index);
+                }
+            }
+            Some(index) => self.values[index] = value.to_string(),
+            None => return self.add(value),
+        }
+        record_id(&self.name, value)
+    }
+
+    /// 移除 id 为 `id` 的值，值不存在时返回 false
+    pub fn remove(&mut self, id: u64) -> Result<bool> {
+        match find_value(&self.name, &self.values, id)? {
+            Some(index) => {
+                self.values.remove(index);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_txt_record_set() {
+        let name = record_name("example.com", "_dnsauth");
+        assert_eq!(name, "_dnsauth.example.com.");
+        assert_eq!(record_name("example.com", "@"), "example.com.");
+        let old_id = record_id(&name, "old").unwrap();
+        assert_eq!(record_id("_DNSAUTH.example.com", "old").unwrap(), old_id);
+
+        let mut record_set = TxtRecordSet::new(&name, 300);
+        record_set.values = vec!["other".to_string(), "old".to_string()];
+        let new_id = record_set.replace(old_id, "new").unwrap();
+        assert_eq!(record_set.values, vec!["other", "new"]);
+        assert_eq!(new_id, record_id(&name, "new").unwrap());
+
+        record_set.replace(old_id, "added").unwrap();
+        assert_eq!(record_set.values, vec!["other", "new", "added"]);
+        record_set.replace(new_id, "other").unwrap();
+        assert_eq!(record_set.values, vec!["other", "added"]);
+
+        assert!(
+            record_set
+                .remove(record_id(&name, "added").unwrap())
+                .unwrap()
+        );
+        assert!(!record_set.remove(old_id).unwrap());
+        assert_eq!(record_set.values, vec!["other"]);
+    }
+}
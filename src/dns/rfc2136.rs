@@ -0,0 +1,431 @@
+use super::{DNS, records};
+use crate::Result;
+use crate::domain::CloudProvider;
+use crate::error::AppError;
+use base64::{Engine as _, engine::general_purpose};
+use hickory_proto::dnssec::rdata::tsig::TsigAlgorithm;
+use hickory_proto::dnssec::tsig::TSigner;
+use hickory_proto::op::{Message, MessageType, OpCode, Query, ResponseCode, UpdateMessage};
+use hickory_proto::rr::rdata::TXT;
+use hickory_proto::rr::{DNSClass, Name, RData, Record, RecordType};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tracing::debug;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_TTL: u32 = 60;
+const TSIG_FUDGE: u16 = 300;
+
+/// 通过 RFC 2136 DNS UPDATE 向主服务器更新记录，适用于 BIND、Knot 等自建 DNS。
+///
+/// `options.server` 为主服务器地址，`secret_id` 为 TSIG 密钥名，`secret_key` 为 base64 编码的密钥，
+/// 密钥名为空时发送不签名的更新请求。`options.algorithm` 默认为 `hmac-sha256`，`options.ttl` 默认为 60
+pub struct Rfc2136DNS {
+    server: String,
+    signer: Option<TSigner>,
+    ttl: u32,
+}
+
+impl Rfc2136DNS {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        let server = provider.options.get("server").ok_or_else(|| {
+            AppError::ConfigError("rfc2136 dns provider requires option: server".to_string())
+        })?;
+        let server = parse_server(server);
+        let ttl = match provider.options.get("ttl") {
+            Some(ttl) => ttl
+                .parse()
+                .map_err(|_| AppError::ConfigError(format!("invalid rfc2136 ttl: {}", ttl)))?,
+            None => DEFAULT_TTL,
+        };
+        let signer = if provider.secret_id.is_empty() {
+            None
+        } else {
+            let algorithm = provider
+                .options
+                .get("algorithm")
+                .map(String::as_str)
+                .unwrap_or("hmac-sha256");
+            let algorithm = Name::from_ascii(algorithm).map_err(|e| {
+                AppError::ConfigError(format!("invalid tsig algorithm {}: {}", algorithm, e))
+            })?;
+            let algorithm = TsigAlgorithm::from_name(algorithm);
+            let key = general_purpose::STANDARD.decode(provider.secret_key.trim())?;
+            let signer = TSigner::new(key, algorithm, parse_name(&provider.secret_id)?, TSIG_FUDGE)
+                .map_err(|e| AppError::ConfigError(format!("invalid tsig key: {}", e)))?;
+            Some(signer)
+        };
+        Ok(Rfc2136DNS {
+            server,
+            signer,
+            ttl,
+        })
+    }
+
+    fn update_message(&self, zone: &Name) -> Result<Message> {
+        let mut message = Message::new();
+        message
+            .set_id(message_id()?)
+            .set_message_type(MessageType::Query)
+            .set_op_code(OpCode::Update)
+            .set_recursion_desired(false);
+        let mut query = Query::new();
+        query
+            .set_name(zone.clone())
+            .set_query_class(DNSClass::IN)
+            .set_query_type(RecordType::SOA);
+        message.add_zone(query);
+        Ok(message)
+    }
+
+    fn txt_record(&self, name: &Name, value: &str) -> Record {
+        Record::from_rdata(
+            name.clone(),
+            self.ttl,
+            RData::TXT(TXT::new(vec![value.to_string()])),
+        )
+    }
+
+    /// 删除记录名下的某一个 TXT 值
+    fn delete_value(&self, name: &Name, value: &str) -> Record {
+        let mut delete = self.txt_record(name, value);
+        delete.set_dns_class(DNSClass::NONE).set_ttl(0);
+        delete
+    }
+
+    /// 从主服务器查询记录名下的全部 TXT 值
+    async fn query_txt(&self, name: &Name) -> Result<Vec<String>> {
+        let mut message = Message::new();
+        message
+            .set_id(message_id()?)
+            .set_message_type(MessageType::Query)
+            .set_op_code(OpCode::Query)
+            .set_recursion_desired(false)
+            .add_query(Query::query(name.clone(), RecordType::TXT));
+        let response = self.send(message).await?;
+        Ok(response
+            .answers()
+            .iter()
+            .filter_map(|answer| match answer.data() {
+                RData::TXT(txt) => Some(
+                    txt.txt_data()
+                        .iter()
+                        .map(|data| String::from_utf8_lossy(data))
+                        .collect::<String>(),
+                ),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// 记录 id 由记录名和记录值生成，查询当前的 TXT 值找到 id 对应的值
+    async fn find_value(&self, name: &Name, record_id: u64) -> Result<Option<String>> {
+        let mut values = self.query_txt(name).await?;
+        Ok(records::find_value(&name.to_ascii(), &values, record_id)?
+            .map(|index| values.swap_remove(index)))
+    }
+
+    /// 通过 TCP 发送请求，配置了 TSIG 时签名请求并校验响应签名
+    async fn send(&self, mut message: Message) -> Result<Message> {
+        let verifier = match &self.signer {
+            Some(signer) => message
+                .finalize(signer, unix_time() as u32)
+                .map_err(|e| AppError::CloudError(format!("tsig sign failed: {}", e)))?,
+            None => None,
+        };
+        let request = message
+            .to_vec()
+            .map_err(|e| AppError::CloudError(e.to_string()))?;
+
+        let response = timeout(TIMEOUT, async {
+            let mut stream = TcpStream::connect(&self.server).await?;
+            stream.write_u16(request.len() as u16).await?;
+            stream.write_all(&request).await?;
+            let len = stream.read_u16().await?;
+            let mut response = vec![0u8; len as usize];
+            stream.read_exact(&mut response).await?;
+            Ok::<_, std::io::Error>(response)
+        })
+        .await
+        .map_err(|_| AppError::CloudError(format!("dns server {} timed out", self.server)))??;
+
+        let response = match verifier {
+            Some(mut verifier) => verifier(&response)
+                .map_err(|e| AppError::CloudError(format!("tsig verify failed: {}", e)))?
+                .into_message(),
+            None => {
+                Message::from_vec(&response).map_err(|e| AppError::CloudError(e.to_string()))?
+            }
+        };
+        if response.id() != message.id() {
+            return Err(AppError::CloudError("dns response id mismatch".to_string()));
+        }
+        if response.response_code() != ResponseCode::NoError {
+            return Err(AppError::CloudError(format!(
+                "dns server {} returned {}",
+                self.server,
+                response.response_code()
+            )));
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl DNS for Rfc2136DNS {
+    async fn add_record(&self, record: &str, domain: &str, sub_domain: &str) -> Result<u64> {
+        let zone = parse_name(domain)?;
+        let name = record_name(sub_domain, &zone)?;
+        let mut message = self.update_message(&zone)?;
+        message.add_update(self.txt_record(&name, record));
+        self.send(message).await?;
+        debug!("Added TXT record {} {}", name, record);
+        records::record_id(&name.to_ascii(), record)
+    }
+
+    /// 在一个 UPDATE 中删除 `record_id` 对应的值并添加新值，同名的其他 TXT 记录保持不变
+    async fn modify_record(
+        &self,
+        record: &str,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<u64> {
+        let zone = parse_name(domain)?;
+        let name = record_name(sub_domain, &zone)?;
+        let mut message = self.update_message(&zone)?;
+        if let Some(value) = self.find_value(&name, record_id).await? {
+            message.add_update(self.delete_value(&name, &value));
+        }
+        message.add_update(self.txt_record(&name, record));
+        self.send(message).await?;
+        records::record_id(&name.to_ascii(), record)
+    }
+
+    /// 查询记录名下的 TXT 值，删除 id 与 `record_id` 一致的值；已不存在时视为删除成功
+    async fn delete_record(
+        &self,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<String> {
+        let zone = parse_name(domain)?;
+        let name = record_name(sub_domain, &zone)?;
+        let Some(value) = self.find_value(&name, record_id).await? else {
+            debug!("TXT record {} {} not found", name, record_id);
+            return Ok(String::new());
+        };
+        let mut message = self.update_message(&zone)?;
+        message.add_update(self.delete_value(&name, &value));
+        let response = self.send(message).await?;
+        Ok(response.id().to_string())
+    }
+
+    /// 从主服务器查询 TXT 记录，存在时返回第一条记录的 id
+    async fn record_id(&self, domain: &str, sub_domain: &str) -> Result<u64> {
+        let zone = parse_name(domain)?;
+        let name = record_name(sub_domain, &zone)?;
+        match self.query_txt(&name).await?.first() {
+            Some(value) => records::record_id(&name.to_ascii(), value),
+            None => Err(AppError::CloudError("no dns record".to_string())),
+        }
+    }
+
+    /// DNS UPDATE 没有列出根域名的方法
+    async fn list_zones(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+/// 服务器地址可以是 `IP:端口`、`[IPv6]:端口`、不带端口的 IPv4/IPv6 地址或主机名，默认端口 53
+fn parse_server(server: &str) -> String {
+    if let Ok(address) = server.parse::<SocketAddr>() {
+        return address.to_string();
+    }
+    if let Ok(ip) = server.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return SocketAddr::new(ip, 53).to_string();
+    }
+    if server.contains(':') {
+        server.to_string()
+    } else {
+        format!("{}:53", server)
+    }
+}
+
+fn parse_name(name: &str) -> Result<Name> {
+    let mut name = Name::from_ascii(name)
+        .map_err(|e| AppError::ConfigError(format!("invalid dns name {}: {}", name, e)))?;
+    name.set_fqdn(true);
+    Ok(name)
+}
+
+fn record_name(sub_domain: &str, zone: &Name) -> Result<Name> {
+    if sub_domain.is_empty() || sub_domain == "@" {
+        return Ok(zone.clone());
+    }
+    let sub_domain = Name::from_ascii(sub_domain)
+        .map_err(|e| AppError::ConfigError(format!("invalid dns name {}: {}", sub_domain, e)))?;
+    sub_domain
+        .append_domain(zone)
+        .map_err(|e| AppError::ConfigError(e.to_string()))
+}
+
+fn message_id() -> Result<u16> {
+    let mut id = [0u8; 2];
+    openssl::rand::rand_bytes(&mut id)?;
+    Ok(u16::from_be_bytes(id))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_proto::dnssec::rdata::tsig::{TSIG, make_tsig_record, message_tbs};
+    use std::collections::BTreeMap;
+    use tokio::net::TcpListener;
+
+    const KEY_NAME: &str = "csu-key.";
+    const SECRET: &str = "c2VjcmV0LWtleS1mb3ItdGVzdGluZy1yZmMyMTM2LXRzaWc=";
+
+    /// 校验请求签名并返回签名响应，记录收到的请求；TXT 查询返回 `values`
+    async fn serve(listener: TcpListener, requests: usize, values: &[&str]) -> Vec<Message> {
+        let key = general_purpose::STANDARD.decode(SECRET).unwrap();
+        let key_name = parse_name(KEY_NAME).unwrap();
+        let signer =
+            TSigner::new(key, TsigAlgorithm::HmacSha256, key_name.clone(), TSIG_FUDGE).unwrap();
+        let mut received = Vec::new();
+        for _ in 0..requests {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let len = stream.read_u16().await.unwrap();
+            let mut request = vec![0u8; len as usize];
+            stream.read_exact(&mut request).await.unwrap();
+            let (request_mac, _, _) = signer.verify_message_byte(None, &request, true).unwrap();
+            let request = Message::from_vec(&request).unwrap();
+
+            let mut response = Message::new();
+            response
+                .set_id(request.id())
+                .set_message_type(MessageType::Response)
+                .set_op_code(request.op_code());
+            if request.op_code() == OpCode::Query {
+                let name = request.queries()[0].name().clone();
+                response.add_query(request.queries()[0].clone());
+                for value in values {
+                    response.add_answer(Record::from_rdata(
+                        name.clone(),
+                        DEFAULT_TTL,
+                        RData::TXT(TXT::new(vec![value.to_string()])),
+                    ));
+                }
+            }
+            let pre_tsig = TSIG::new(
+                TsigAlgorithm::HmacSha256,
+                unix_time(),
+                TSIG_FUDGE,
+                Vec::new(),
+                request.id(),
+                0,
+                Vec::new(),
+            );
+            // message_tbs 把请求签名写在同一个编码器中，会使响应中的压缩指针偏移，这里单独拼接
+            let mut tbs = (request_mac.len() as u16).to_be_bytes().to_vec();
+            tbs.extend_from_slice(&request_mac);
+            tbs.extend(message_tbs(None, &response, &pre_tsig, &key_name).unwrap());
+            let mac = signer.sign(&tbs).unwrap();
+            response.add_tsig(make_tsig_record(key_name.clone(), pre_tsig.set_mac(mac)));
+            let response = response.to_vec().unwrap();
+            stream.write_u16(response.len() as u16).await.unwrap();
+            stream.write_all(&response).await.unwrap();
+            received.push(request);
+        }
+        received
+    }
+
+    fn provider(server: String) -> CloudProvider {
+        CloudProvider {
+            name: "rfc2136".to_string(),
+            secret_id: KEY_NAME.to_string(),
+            secret_key: SECRET.to_string(),
+            options: BTreeMap::from([("server".to_string(), server)]),
+            ..Default::default()
+        }
+    }
+
+    /// 删除时查询当前的 TXT 值，只删除 id 对应的值
+    #[tokio::test]
+    async fn test_rfc2136_add_and_delete_record() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+        let handle = tokio::spawn(async move { serve(listener, 3, &["other", "token"]).await });
+
+        let dns = Rfc2136DNS::new(&provider(server)).unwrap();
+        let record_id = dns
+            .add_record("token", "example.com", "_dnsauth.www")
+            .await
+            .unwrap();
+        dns.delete_record(record_id, "example.com", "_dnsauth.www")
+            .await
+            .unwrap();
+
+        let received = handle.await.unwrap();
+        let added = &received[0].name_servers()[0];
+        assert_eq!(added.name().to_ascii(), "_dnsauth.www.example.com.");
+        assert_eq!(added.dns_class(), DNSClass::IN);
+        assert_eq!(received[1].op_code(), OpCode::Query);
+        assert_eq!(received[2].name_servers().len(), 1);
+        let deleted = &received[2].name_servers()[0];
+        assert_eq!(deleted.dns_class(), DNSClass::NONE);
+        assert_eq!(deleted.data(), added.data());
+    }
+
+    /// 修改时只替换 id 对应的值，返回新值的 id
+    #[tokio::test]
+    async fn test_rfc2136_modify_record() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+        let handle = tokio::spawn(async move { serve(listener, 2, &["other", "old"]).await });
+
+        let dns = Rfc2136DNS::new(&provider(server)).unwrap();
+        let name = "_dnsauth.example.com.";
+        let old_id = records::record_id(name, "old").unwrap();
+        let new_id = dns
+            .modify_record("new", old_id, "example.com", "_dnsauth")
+            .await
+            .unwrap();
+        assert_eq!(new_id, records::record_id(name, "new").unwrap());
+
+        let received = handle.await.unwrap();
+        let updates = received[1].name_servers();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].dns_class(), DNSClass::NONE);
+        assert_eq!(
+            updates[0].data(),
+            &RData::TXT(TXT::new(vec!["old".to_string()]))
+        );
+        assert_eq!(updates[1].dns_class(), DNSClass::IN);
+        assert_eq!(
+            updates[1].data(),
+            &RData::TXT(TXT::new(vec!["new".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_parse_server() {
+        assert_eq!(parse_server("192.0.2.1"), "192.0.2.1:53");
+        assert_eq!(parse_server("192.0.2.1:5353"), "192.0.2.1:5353");
+        assert_eq!(parse_server("2001:db8::1"), "[2001:db8::1]:53");
+        assert_eq!(parse_server("[2001:db8::1]"), "[2001:db8::1]:53");
+        assert_eq!(parse_server("[2001:db8::1]:5353"), "[2001:db8::1]:5353");
+        assert_eq!(parse_server("ns1.example.com"), "ns1.example.com:53");
+        assert_eq!(parse_server("ns1.example.com:5353"), "ns1.example.com:5353");
+    }
+}
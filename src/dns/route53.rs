@@ -0,0 +1,338 @@
+use super::DNS;
+use super::records::{self, TxtRecordSet, record_name};
+use crate::Result;
+use crate::aws::{AwsClient, xml_elements, xml_escape, xml_text, xml_texts};
+use crate::domain::{CloudProvider, Endpoint};
+use crate::error::AppError;
+use reqwest::{Method, Url};
+use tokio::time::{Duration, Instant, sleep};
+use tracing::{debug, info};
+
+const API_VERSION: &str = "2013-04-01";
+const DEFAULT_ENDPOINT: &str = "https://route53.amazonaws.com";
+const DEFAULT_TTL: u64 = 60;
+/// 变更通常在一分钟内同步到全部权威服务器
+const INSYNC_TIMEOUT: Duration = Duration::from_secs(600);
+const INSYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// AWS Route 53，`options.route53_endpoint` 可覆盖接口地址
+pub struct Route53DNS {
+    client: AwsClient,
+    endpoint: Endpoint,
+}
+
+impl Route53DNS {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        Ok(Route53DNS {
+            client: AwsClient::new(provider),
+            endpoint: provider.endpoint("route53_endpoint", DEFAULT_ENDPOINT)?,
+        })
+    }
+
+    fn url(&self, path: &str) -> Result<Url> {
+        self.endpoint.url(&format!("/{}/{}", API_VERSION, path))
+    }
+
+    async fn get(&self, url: Url) -> Result<String> {
+        let response = self
+            .client
+            .request("route53", Method::GET, url, &[], String::new())
+            .await?;
+        Ok(response.body)
+    }
+
+    async fn hosted_zone_id(&self, domain: &str) -> Result<String> {
+        let mut url = self.url("hostedzonesbyname")?;
+        url.query_pairs_mut()
+            .append_pair("dnsname", domain)
+            .append_pair("maxitems", "1");
+        let body = self.get(url).await?;
+        xml_elements(&body, "HostedZone")
+            .into_iter()
+            .find(|zone| {
+                xml_text(zone, "Name").is_some_and(|name| name.trim_end_matches('.') == domain)
+            })
+            .and_then(|zone| xml_text(zone, "Id"))
+            .map(|id| id.trim_start_matches("/hostedzone/").to_string())
+            .ok_or_else(|| {
+                AppError::CloudError(format!("route53 hosted zone not found: {}", domain))
+            })
+    }
+
+    async fn txt_record_set(&self, zone_id: &str, name: &str) -> Result<Option<TxtRecordSet>> {
+        let mut url = self.url(&format!("hostedzone/{}/rrset", zone_id))?;
+        url.query_pairs_mut()
+            .append_pair("name", name)
+            .append_pair("type", "TXT")
+            .append_pair("maxitems", "1");
+        let body = self.get(url).await?;
+        let record_set = xml_elements(&body, "ResourceRecordSet")
+            .into_iter()
+            .find(|record_set| {
+                xml_text(record_set, "Name").is_some_and(|record_name| {
+                    record_name.trim_end_matches('.') == name.trim_end_matches('.')
+                }) && xml_text(record_set, "Type").as_deref() == Some("TXT")
+            });
+        Ok(record_set.map(|record_set| TxtRecordSet {
+            name: name.to_string(),
+            ttl: xml_text(record_set, "TTL")
+                .and_then(|ttl| ttl.parse().ok())
+                .unwrap_or(DEFAULT_TTL),
+            values: xml_texts(record_set, "Value")
+                .iter()
+                .map(|value| value.trim_matches('"').to_string())
+                .collect(),
+        }))
+    }
+
+    /// 记录名下当前的记录集，不存在时为空记录集
+    async fn record_set(
+        &self,
+        zone_id: &str,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<TxtRecordSet> {
+        let name = record_name(domain, sub_domain);
+        Ok(self
+            .txt_record_set(zone_id, &name)
+            .await?
+            .unwrap_or_else(|| TxtRecordSet::new(&name, DEFAULT_TTL)))
+    }
+
+    /// 提交变更并等待同步到全部权威服务器，返回变更 id
+    async fn change(
+        &self,
+        zone_id: &str,
+        action: &str,
+        record_set: &TxtRecordSet,
+    ) -> Result<String> {
+        let records: String = record_set
+            .values
+            .iter()
+            .map(|value| {
+                format!(
+                    "<ResourceRecord><Value>{}</Value></ResourceRecord>",
+                    xml_escape(&format!("\"{}\"", value))
+                )
+            })
+            .collect();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ChangeResourceRecordSetsRequest xmlns="https://route53.amazonaws.com/doc/{}/"><ChangeBatch><Changes><Change><Action>{}</Action><ResourceRecordSet><Name>{}</Name><Type>TXT</Type><TTL>{}</TTL><ResourceRecords>{}</ResourceRecords></ResourceRecordSet></Change></Changes></ChangeBatch></ChangeResourceRecordSetsRequest>"#,
+            API_VERSION,
+            action,
+            xml_escape(&record_set.name),
+            record_set.ttl,
+            records
+        );
+        let url = self.url(&format!("hostedzone/{}/rrset", zone_id))?;
+        let response = self
+            .client
+            .request(
+                "route53",
+                Method::POST,
+                url,
+                &[("content-type", "application/xml")],
+                body,
+            )
+            .await?;
+        let change_id = xml_text(&response.body, "Id")
+            .map(|id| id.trim_start_matches("/change/").to_string())
+            .ok_or_else(|| AppError::CloudError("route53 change id missing".to_string()))?;
+        self.wait_insync(&change_id).await?;
+        Ok(change_id)
+    }
+
+    async fn wait_insync(&self, change_id: &str) -> Result<()> {
+        let deadline = Instant::now() + INSYNC_TIMEOUT;
+        loop {
+            let body = self
+                .get(self.url(&format!("change/{}", change_id))?)
+                .await?;
+            let status = xml_text(&body, "Status").unwrap_or_default();
+            debug!("Route53 change {} status: {}", change_id, status);
+            if status == "INSYNC" {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(AppError::CloudError(format!(
+                    "route53 change {} not in sync after {} seconds",
+                    change_id,
+                    INSYNC_TIMEOUT.as_secs()
+                )));
+            }
+            sleep(INSYNC_INTERVAL).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DNS for Route53DNS {
+    async fn add_record(&self, record: &str, domain: &str, sub_domain: &str) -> Result<u64> {
+        let zone_id = self.hosted_zone_id(domain).await?;
+        let mut record_set = self.record_set(&zone_id, domain, sub_domain).await?;
+        let record_id = record_set.add(record)?;
+        self.change(&zone_id, "UPSERT", &record_set).await?;
+        info!("Added route53 TXT record {}", record_set.name);
+        Ok(record_id)
+    }
+
+    async fn modify_record(
+        &self,
+        record: &str,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<u64> {
+        let zone_id = self.hosted_zone_id(domain).await?;
+        let mut record_set = self.record_set(&zone_id, domain, sub_domain).await?;
+        let record_id = record_set.replace(record_id, record)?;
+        self.change(&zone_id, "UPSERT", &record_set).await?;
+        Ok(record_id)
+    }
+
+    /// 移除最后一个值时删除整个记录集，记录已不存在时视为删除成功
+    async fn delete_record(
+        &self,
+        record_id: u64,
+        domain: &str,
+        sub_domain: &str,
+    ) -> Result<String> {
+        let zone_id = self.hosted_zone_id(domain).await?;
+        let name = record_name(domain, sub_domain);
+        let Some(mut record_set) = self.txt_record_set(&zone_id, &name).await? else {
+            return Ok(String::new());
+        };
+        let values = record_set.values.clone();
+        if !record_set.remove(record_id)? {
+            return Ok(String::new());
+        }
+        if record_set.values.is_empty() {
+            // DELETE 需要提交与当前完全一致的记录集
+            record_set.values = values;
+            return self.change(&zone_id, "DELETE", &record_set).await;
+        }
+        self.change(&zone_id, "UPSERT", &record_set).await
+    }
+
+    async fn record_id(&self, domain: &str, sub_domain: &str) -> Result<u64> {
+        let zone_id = self.hosted_zone_id(domain).await?;
+        let name = record_name(domain, sub_domain);
+        match self
+            .txt_record_set(&zone_id, &name)
+            .await?
+            .and_then(|record_set| record_set.values.into_iter().next())
+        {
+            Some(value) => records::record_id(&name, &value),
+            None => Err(AppError::CloudError("no dns record".to_string())),
+        }
+    }
+
+    async fn list_zones(&self) -> Result<Vec<String>> {
+        let mut zones = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let mut url = self.url("hostedzone")?;
+            if let Some(marker) = &marker {
+                url.query_pairs_mut().append_pair("marker", marker);
+            }
+            let body = self.get(url).await?;
+            zones.extend(
+                xml_elements(&body, "HostedZone")
+                    .into_iter()
+                    .filter_map(|zone| xml_text(zone, "Name"))
+                    .map(|name| name.trim_end_matches('.').to_string()),
+            );
+            marker = xml_text(&body, "NextMarker");
+            if xml_text(&body, "IsTruncated").as_deref() != Some("true") || marker.is_none() {
+                break;
+            }
+        }
+        Ok(zones)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 按顺序响应查询托管区域、查询记录集、提交变更和查询变更状态，返回收到的请求
+    async fn serve(listener: TcpListener, responses: Vec<&'static str>) -> Vec<String> {
+        let mut requests = Vec::new();
+        for body in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut chunk).await.unwrap();
+                buffer.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&buffer).to_string();
+                if let Some((head, content)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if content.len() >= length || n == 0 {
+                        break;
+                    }
+                }
+            }
+            requests.push(String::from_utf8_lossy(&buffer).to_string());
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/xml\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        requests
+    }
+
+    /// 修改时只替换记录集中对应的值，保留其他值和原有 TTL
+    #[tokio::test]
+    async fn test_modify_record_keeps_other_values_and_ttl() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(serve(
+            listener,
+            vec![
+                "<ListHostedZonesByNameResponse><HostedZones><HostedZone><Id>/hostedzone/Z1</Id><Name>example.com.</Name></HostedZone></HostedZones></ListHostedZonesByNameResponse>",
+                "<ListResourceRecordSetsResponse><ResourceRecordSets><ResourceRecordSet><Name>_dnsauth.example.com.</Name><Type>TXT</Type><TTL>300</TTL><ResourceRecords><ResourceRecord><Value>\"other\"</Value></ResourceRecord><ResourceRecord><Value>\"old\"</Value></ResourceRecord></ResourceRecords></ResourceRecordSet></ResourceRecordSets></ListResourceRecordSetsResponse>",
+                "<ChangeResourceRecordSetsResponse><ChangeInfo><Id>/change/C1</Id><Status>PENDING</Status></ChangeInfo></ChangeResourceRecordSetsResponse>",
+                "<GetChangeResponse><ChangeInfo><Id>/change/C1</Id><Status>INSYNC</Status></ChangeInfo></GetChangeResponse>",
+            ],
+        ));
+
+        let provider = CloudProvider {
+            name: "aws".to_string(),
+            secret_id: "AKIA".to_string(),
+            secret_key: "SECRET".to_string(),
+            options: BTreeMap::from([("route53_endpoint".to_string(), endpoint)]),
+            ..Default::default()
+        };
+        let dns = Route53DNS::new(&provider).unwrap();
+        let name = "_dnsauth.example.com.";
+        let old_id = records::record_id(name, "old").unwrap();
+        let new_id = dns
+            .modify_record("new", old_id, "example.com", "_dnsauth")
+            .await
+            .unwrap();
+        assert_eq!(new_id, records::record_id(name, "new").unwrap());
+
+        let requests = handle.await.unwrap();
+        let change = &requests[2];
+        assert!(change.starts_with("POST /2013-04-01/hostedzone/Z1/rrset"));
+        assert!(change.contains("<Action>UPSERT</Action>"));
+        assert!(change.contains("<TTL>300</TTL>"));
+        assert!(change.contains(&xml_escape("\"other\"")));
+        assert!(change.contains(&xml_escape("\"new\"")));
+        assert!(!change.contains(&xml_escape("\"old\"")));
+    }
+}
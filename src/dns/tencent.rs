@@ -1,6 +1,8 @@
 use super::DNS;
 use crate::Result;
 use crate::error::AppError;
+use crate::tencent::{Action, TencentClient};
+use serde_json::json;
 use tencent_sdk::{
     client::TencentCloudAsync,
     core::TencentCloudResult,
@@ -9,8 +11,13 @@ use tencent_sdk::{
     transport::async_impl::ReqwestAsync,
 };
 
+const DNSPOD_API_VERSION: &str = "2021-03-23";
+/// DescribeDomainList 单页最多返回 3000 条
+const DESCRIBE_DOMAIN_LIST_LIMIT: u64 = 3000;
+
 pub struct TencentDNS {
     pub client: TencentCloudAsync<RetryAsync<ReqwestAsync>>,
+    api: TencentClient,
 }
 
 impl TencentDNS {
@@ -21,7 +28,10 @@ impl TencentDNS {
             .with_retry(3, std::time::Duration::from_millis(200))
             .build()?;
 
-        Ok(TencentDNS { client })
+        Ok(TencentDNS {
+            client,
+            api: TencentClient::new(secret_id, secret_key),
+        })
     }
 }
 
@@ -56,7 +66,12 @@ impl DNS for TencentDNS {
         }
     }
 
-    async fn delete_record(&self, record_id: u64, domain: &str) -> Result<String> {
+    async fn delete_record(
+        &self,
+        record_id: u64,
+        domain: &str,
+        _sub_domain: &str,
+    ) -> Result<String> {
         let request = DeleteRecord::new(domain, record_id);
         match self.client.request(&request).await {
             Ok(response) => Ok(response.response.request_id),
@@ -76,4 +91,41 @@ impl DNS for TencentDNS {
             Err(e) => Err(AppError::CloudError(e.to_string())),
         }
     }
+
+    /// DescribeDomainList 不在固定版本的 SDK 中，通过 [`TencentClient`] 调用
+    async fn list_zones(&self) -> Result<Vec<String>> {
+        let mut zones = Vec::new();
+        let mut offset = 0;
+        loop {
+            let response = self
+                .api
+                .request(
+                    &Action {
+                        service: "dnspod",
+                        version: DNSPOD_API_VERSION,
+                        action: "DescribeDomainList",
+                    },
+                    &json!({ "Offset": offset, "Limit": DESCRIBE_DOMAIN_LIST_LIMIT }),
+                )
+                .await?;
+            let page = response["DomainList"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let total = response["DomainCountInfo"]["AllTotal"]
+                .as_u64()
+                .unwrap_or_default();
+            offset += page.len() as u64;
+            zones.extend(
+                page.iter()
+                    .filter_map(|item| item["Name"].as_str())
+                    .map(str::to_string),
+            );
+
+            if page.is_empty() || offset >= total {
+                break;
+            }
+        }
+        Ok(zones)
+    }
 }
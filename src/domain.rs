@@ -1,46 +1,311 @@
 use crate::Result;
-use crate::cdn::CDN;
+use crate::cdn::HttpsOptions;
+use crate::deploy::{CertificateSource, DeployTarget, IssuedCertificate, VerifyOptions};
 use crate::dns::DNS;
-use crate::ssl::{ApplyStatus, CertificateInfo, SSL, parse_cert_from_base64};
+use crate::error::AppError;
+use crate::report::{DeployResult, PruneResult};
+use crate::ssl::{
+    ApplyStatus, CertBundle, CertificateInfo, CertificateSummary, DnsChallenge, FileChallenge,
+    KeyAlgorithm, RENEW_BEFORE_DAYS, SSL, name_covered, parse_cert_from_base64,
+};
+use crate::store::{DomainState, Store};
+use crate::validation::{FileValidation, Publisher};
+use crate::zone::{find_zone, resolve_cname};
+use reqwest::Url;
 use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::time::{Duration, sleep};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Domain {
     pub name: String,
+    /// 证书额外包含的域名，支持通配符，如 `*.example.com`
+    #[serde(default)]
+    pub sans: Vec<String>,
+    /// 域名所在的 DNS 根域名，为空时按 DNS 账号托管的域名或公共后缀列表自动确定
+    #[serde(default)]
     pub original_name: String,
     pub ssl_provider: CloudProvider,
-    pub cdn_provider: CloudProvider,
-    pub dns_provider: CloudProvider,
+    pub cdn_provider: Option<CloudProvider>,
+    /// 使用文件验证时可以省略
+    pub dns_provider: Option<CloudProvider>,
+    /// 本地生成私钥并提交 CSR。目前没有 SSL 服务商接受 CSR，设置后加载域名配置时报错
+    pub local_key: Option<KeyAlgorithm>,
+    /// 按顺序部署的目标，`cdn_provider` 若存在会作为第一个目标
+    #[serde(default)]
+    pub targets: Vec<DeployTarget>,
+    /// 设置后验证记录添加到 CNAME 委托的根域名，不需要原根域名的写权限
+    pub cname_delegation: Option<CnameDelegation>,
+    /// 设置后使用文件验证（FILE）申请证书，验证文件按该方式发布，不需要 DNS 权限
+    pub file_validation: Option<FileValidation>,
+    /// 部署到 CDN 后检查证书是否生效，默认关闭
+    #[serde(default)]
+    pub verify: VerifyOptions,
+    /// 部署到 CDN 时显式设置的 HTTPS 选项
+    #[serde(default)]
+    pub https: HttpsOptions,
+    /// 设置后全部目标部署成功时清理旧证书，保留最近签发的 N 个
+    pub prune_keep: Option<usize>,
     pub ssl_info: Option<ApplyStatus>,
     pub dns_info: Option<DnsInfo>,
     pub certificate_info: Option<CertificateInfo>,
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct CloudProvider {
+    #[serde(default)]
     pub name: String,
+    #[serde(default)]
     pub secret_id: String,
+    #[serde(default)]
     pub secret_key: String,
+    /// 引用 profiles 文件中的凭证，加载域名配置时替换为实际凭证
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// 服务商特有的配置，如 hook 的命令、区域或接口地址
+    #[serde(default)]
+    pub options: BTreeMap<String, String>,
+}
+
+/// 只比较凭证和服务商配置，同一账号无论是否通过 profile 引用都视为相同
+impl PartialEq for CloudProvider {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.secret_id == other.secret_id
+            && self.secret_key == other.secret_key
+            && self.options == other.options
+    }
+}
+
+impl Eq for CloudProvider {}
+
+/// 可以签发证书的 SSL 服务商
+pub(crate) const SSL_PROVIDERS: &[&str] = &["tencent"];
+pub(crate) const DNS_PROVIDERS: &[&str] = &["tencent", "hook", "rfc2136", "aws", "huawei"];
+pub(crate) const CDN_PROVIDERS: &[&str] = &["tencent", "aws", "huawei"];
+/// 部署到 CDN 前可以上传证书的 SSL 服务，ACM 和华为云 SCM 只能导入证书，不能用作 `ssl_provider`
+const UPLOAD_PROVIDERS: &[&str] = &["tencent", "aws", "huawei"];
+/// 能签发多域名和通配符证书的 SSL 服务商。腾讯云免费证书只支持单个非通配符域名，目前没有其他签发服务商
+const MULTI_DOMAIN_SSL_PROVIDERS: &[&str] = &[];
+/// 通过 AK/SK 调用接口的服务商，hook 和 rfc2136 的凭证可以为空
+const CREDENTIAL_PROVIDERS: &[&str] = &["tencent", "aws", "huawei"];
+
+impl CloudProvider {
+    /// 检查服务商名称是否受支持，以及是否填写了凭证；引用 profile 时凭证由 profile 提供
+    fn validate(&self, domain: &str, field: &str, supported: &[&str]) -> Result<()> {
+        if !supported.contains(&self.name.as_str()) {
+            return Err(AppError::ConfigError(format!(
+                "domain {}: {}.name must be one of {}, got {:?}",
+                domain,
+                field,
+                supported.join(", "),
+                self.name
+            )));
+        }
+        if self.profile.is_none()
+            && CREDENTIAL_PROVIDERS.contains(&self.name.as_str())
+            && (self.secret_id.is_empty() || self.secret_key.is_empty())
+        {
+            return Err(AppError::ConfigError(format!(
+                "domain {}: {} requires secret_id and secret_key or a profile",
+                domain, field
+            )));
+        }
+        Ok(())
+    }
+
+    /// 账号标识，格式为 `服务商:profile`；未引用 profile 时使用脱敏后的 secret_id
+    pub fn account(&self) -> String {
+        match &self.profile {
+            Some(profile) => format!("{}:{}", self.name, profile),
+            None => format!("{}:{}", self.name, mask_secret_id(&self.secret_id)),
+        }
+    }
+
+    /// 接口地址，`options` 中设置了 `key` 时使用该地址，便于本地测试
+    pub fn endpoint(&self, key: &str, default: &str) -> Result<Endpoint> {
+        let endpoint = self.options.get(key).map(String::as_str).unwrap_or(default);
+        Url::parse(endpoint)
+            .map(Endpoint)
+            .map_err(|e| AppError::ConfigError(format!("{}: {}", endpoint, e)))
+    }
+
+    /// 列出账号中的证书，并标注同一账号下使用该证书的 CDN 域名
+    pub async fn list_certificates(&self) -> Result<Vec<CertificateSummary>> {
+        let account = self.account();
+        let ssl_client = crate::ssl_client(self)?;
+        let mut certificates = ssl_client.list().await?;
+
+        // 查询 CDN 失败不影响证书列表
+        let cdn_client = crate::cdn_client(self)?;
+        let https_configs = match cdn_client.list_https().await {
+            Ok(configs) => configs,
+            Err(e) => {
+                warn!("Failed to list CDN domains for account {}: {}", account, e);
+                Vec::new()
+            }
+        };
+
+        for certificate in &mut certificates {
+            certificate.account = account.clone();
+            certificate.cdn_domains = https_configs
+                .iter()
+                .filter(|config| {
+                    config.certificate_id.as_ref() == Some(&certificate.certificate_id)
+                })
+                .map(|config| config.domain.clone())
+                .collect();
+        }
+        Ok(certificates)
+    }
+}
+
+/// 服务商接口地址
+#[derive(Debug, Clone)]
+pub struct Endpoint(Url);
+
+impl Endpoint {
+    /// 拼接接口路径，以 `/` 开头的路径替换地址中的路径
+    pub fn url(&self, path: &str) -> Result<Url> {
+        self.0
+            .join(path)
+            .map_err(|e| AppError::ConfigError(e.to_string()))
+    }
+}
+
+/// 只保留前后各 4 个字符，较短时全部隐藏
+fn mask_secret_id(secret_id: &str) -> String {
+    let chars: Vec<char> = secret_id.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}****{}", head, tail)
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct DnsInfo {
     pub dns_status: u8,
-    pub dns_record_id: Option<u64>,
+    /// 兼容旧配置中只记录 ID 的 `dns_record_ids`
+    #[serde(default, alias = "dns_record_ids")]
+    pub dns_records: Vec<DnsRecord>,
 }
 
-impl Domain {
-    pub fn can_direct_update_ssl(&self) -> bool {
-        self.ssl_provider == self.cdn_provider
+/// 已添加的验证记录及其所在的根域名
+#[derive(Debug, Deserialize, Clone)]
+#[serde(from = "DnsRecordConfig")]
+pub struct DnsRecord {
+    pub record_id: u64,
+    /// 为空时表示记录在域名自身的根域名中
+    pub zone: String,
+    /// 主机记录，旧配置中为空
+    pub sub_domain: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DnsRecordConfig {
+    Id(u64),
+    Record {
+        record_id: u64,
+        zone: String,
+        #[serde(default)]
+        sub_domain: String,
+    },
+}
+
+impl From<DnsRecordConfig> for DnsRecord {
+    fn from(config: DnsRecordConfig) -> Self {
+        match config {
+            DnsRecordConfig::Id(record_id) => DnsRecord {
+                record_id,
+                zone: String::new(),
+                sub_domain: String::new(),
+            },
+            DnsRecordConfig::Record {
+                record_id,
+                zone,
+                sub_domain,
+            } => DnsRecord {
+                record_id,
+                zone,
+                sub_domain,
+            },
+        }
     }
+}
+
+/// 验证记录通过 CNAME 委托到 dns_provider 管理的其他根域名（acme-dns 方式），
+/// 原根域名中需预先添加 `_dnsauth.<域名>` 或 `_acme-challenge.<域名>` 的 CNAME 记录
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CnameDelegation {
+    /// 委托目标，为空时查询验证记录名的 CNAME
+    pub target: Option<String>,
+    /// 委托目标所在的根域名，为空时按 dns_provider 托管的域名确定
+    pub zone: Option<String>,
+}
 
+impl Domain {
     pub fn name(&self) -> String {
         self.name.clone()
     }
 
+    /// 证书包含的全部域名，`name` 在第一位
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![self.name.clone()];
+        for san in &self.sans {
+            if !names.contains(san) {
+                names.push(san.clone());
+            }
+        }
+        names
+    }
+
+    /// 检查服务商配置以及 SSL 服务商是否支持配置的域名，加载域名配置时调用。
+    /// SSL 服务商不在 [`MULTI_DOMAIN_SSL_PROVIDERS`] 中时只能使用单个非通配符域名，不能设置 `sans`；
+    /// 目前没有 SSL 服务商接受 CSR，不能设置 `local_key`
+    pub fn validate(&self) -> Result<()> {
+        self.ssl_provider
+            .validate(&self.name, "ssl_provider", SSL_PROVIDERS)?;
+        // 文件验证不需要 DNS 权限
+        match &self.dns_provider {
+            Some(provider) => provider.validate(&self.name, "dns_provider", DNS_PROVIDERS)?,
+            None if self.file_validation.is_none() => {
+                return Err(AppError::ConfigError(format!(
+                    "domain {}: dns_provider is required unless file_validation is set",
+                    self.name
+                )));
+            }
+            None => {}
+        }
+        for target in self.deploy_targets() {
+            if let DeployTarget::Cdn(provider) = target {
+                provider.validate(&self.name, "cdn_provider", CDN_PROVIDERS)?;
+                provider.validate(&self.name, "cdn_provider", UPLOAD_PROVIDERS)?;
+            }
+        }
+        if !MULTI_DOMAIN_SSL_PROVIDERS.contains(&self.ssl_provider.name.as_str())
+            && (self.names().len() > 1 || self.name.starts_with("*."))
+        {
+            return Err(AppError::ConfigError(format!(
+                "domain {}: ssl_provider {} only issues single non-wildcard certificates, sans and wildcard names are not supported",
+                self.name, self.ssl_provider.name
+            )));
+        }
+        if self.local_key.is_some() {
+            return Err(AppError::ConfigError(format!(
+                "domain {}: ssl_provider {} does not accept a CSR, local_key is not supported",
+                self.name, self.ssl_provider.name
+            )));
+        }
+        Ok(())
+    }
+
     pub fn original_name(&self) -> String {
         self.original_name.clone()
     }
@@ -52,24 +317,34 @@ impl Domain {
     }
 
     pub fn ssl_client(&self) -> Result<Arc<dyn SSL>> {
-        let secret_id = &self.ssl_provider.secret_id;
-        let secret_key = &self.ssl_provider.secret_key;
-        let provider = &self.ssl_provider.name;
-        crate::ssl_client(provider, secret_id, secret_key)
+        crate::ssl_client(&self.ssl_provider)
+    }
+
+    /// 配置了 `file_validation` 时使用文件验证，否则使用 DNS 验证
+    pub fn validation_method(&self) -> &'static str {
+        match self.file_validation {
+            Some(_) => "FILE",
+            None => "DNS",
+        }
     }
 
     pub fn dns_client(&self) -> Result<Arc<dyn DNS>> {
-        let secret_id = &self.dns_provider.secret_id;
-        let secret_key = &self.dns_provider.secret_key;
-        let provider = &self.dns_provider.name;
-        crate::dns_client(provider, secret_id, secret_key)
+        match &self.dns_provider {
+            Some(provider) => crate::dns_client(provider),
+            None => Err(AppError::ConfigError(format!(
+                "domain {}: dns_provider is not configured",
+                self.name
+            ))),
+        }
     }
 
-    pub fn cdn_client(&self) -> Result<Arc<dyn CDN>> {
-        let secret_id = &self.cdn_provider.secret_id;
-        let secret_key = &self.cdn_provider.secret_key;
-        let provider = &self.cdn_provider.name;
-        crate::cdn_client(provider, secret_id, secret_key)
+    pub fn deploy_targets(&self) -> Vec<DeployTarget> {
+        let mut targets = Vec::with_capacity(self.targets.len() + 1);
+        if let Some(provider) = &self.cdn_provider {
+            targets.push(DeployTarget::Cdn(provider.clone()));
+        }
+        targets.extend(self.targets.iter().cloned());
+        targets
     }
 
     pub fn set_dns_info(&mut self, info: DnsInfo) {
@@ -89,21 +364,162 @@ impl Domain {
 
     pub async fn apply_ssl(&mut self, dv_auth_method: &str) -> Result<()> {
         let ssl_client = self.ssl_client()?;
-        let certificate_id = ssl_client.apply(&self.name, dv_auth_method).await?;
+        let certificate_id = ssl_client.apply(&self.names(), dv_auth_method).await?;
         info!(
             "Applied SSL certificate for domain {}: {}",
-            self.name, certificate_id
+            self.names().join(","),
+            certificate_id
         );
         self.ssl_info = Some(ApplyStatus {
             certificate_id,
-            dns_key: "".to_string(),
-            dns_value: "".to_string(),
+            dns_challenges: Vec::new(),
+            file_challenges: Vec::new(),
             status: 0,
             can_download: false,
         });
         Ok(())
     }
 
+    /// 通过第一个 CDN 目标的接口读取当前证书的到期时间，域名不在 CDN 中或未开启 HTTPS 时返回 None。
+    /// 签发机构显示为服务商及证书 id
+    pub async fn cdn_certificate_info(&self) -> Result<Option<CertificateInfo>> {
+        let Some(provider) = self
+            .deploy_targets()
+            .into_iter()
+            .find_map(|target| match target {
+                DeployTarget::Cdn(provider) => Some(provider),
+                _ => None,
+            })
+        else {
+            return Ok(None);
+        };
+        let cdn_client = crate::cdn_client(&provider)?;
+        let Some(config) = cdn_client.https_config(&self.name).await? else {
+            warn!(
+                "Domain {} not found in {} CDN account",
+                self.name, provider.name
+            );
+            return Ok(None);
+        };
+        if !config.https {
+            warn!("HTTPS is not enabled for CDN domain {}", self.name);
+            return Ok(None);
+        }
+        let (Some(certificate_id), Some(valid_to)) = (config.certificate_id, config.expires_at)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(CertificateInfo {
+            domain: self.name.clone(),
+            issuer: format!("{}:{}", provider.name, certificate_id),
+            valid_from: config.deployed_at.unwrap_or(valid_to),
+            valid_to,
+            days_remaining: (valid_to - chrono::Utc::now()).num_days(),
+            serial: String::new(),
+        }))
+    }
+
+    /// 在 SSL 服务商账号中查找已签发、覆盖全部域名且剩余有效期超过更新阈值的证书，
+    /// 有多个时选择到期最晚的；使用本地私钥时只考虑本地保存了私钥的证书
+    pub async fn find_reusable_certificate(&self, store: &Store) -> Result<Option<String>> {
+        let ssl_client = self.ssl_client()?;
+        let names = self.names();
+        let certificate = ssl_client
+            .list()
+            .await?
+            .into_iter()
+            .filter(|certificate| certificate.issued)
+            .filter(|certificate| {
+                certificate
+                    .days_remaining()
+                    .is_some_and(|days| days > RENEW_BEFORE_DAYS)
+            })
+            .filter(|certificate| {
+                names
+                    .iter()
+                    .all(|name| name_covered(&certificate.domains, name))
+            })
+            .filter(|certificate| {
+                !store
+                    .is_revoked(&certificate.certificate_id)
+                    .unwrap_or(false)
+            })
+            .max_by_key(|certificate| certificate.expires_at);
+        Ok(certificate.map(|certificate| certificate.certificate_id))
+    }
+
+    /// 清理 ssl_provider 及各 CDN 目标账号中该域名的旧证书。
+    /// 按到期时间保留最新的 `keep` 个，其余已过期或已被替换的证书在未绑定 CDN 且不是当前证书时删除
+    pub async fn prune_certificates(
+        &self,
+        store: &Store,
+        keep: usize,
+        dry_run: bool,
+    ) -> Result<Vec<PruneResult>> {
+        let mut providers = vec![self.ssl_provider.clone()];
+        for target in self.deploy_targets() {
+            if let DeployTarget::Cdn(provider) = target
+                && !providers.contains(&provider)
+            {
+                providers.push(provider);
+            }
+        }
+        let current_certificate_id = store.current_certificate_id(&self.name)?;
+        let names = self.names();
+
+        let mut results = Vec::new();
+        for provider in providers {
+            let certificates = provider.list_certificates().await?;
+            let ssl_client = crate::ssl_client(&provider)?;
+            for (certificate, reason) in prune_candidates(
+                certificates,
+                &names,
+                keep,
+                current_certificate_id.as_deref(),
+            ) {
+                let result = if dry_run {
+                    PruneResult::pending(
+                        &self.name,
+                        &certificate.account,
+                        &certificate.certificate_id,
+                        reason,
+                    )
+                } else {
+                    match ssl_client.delete(&certificate.certificate_id).await {
+                        Ok(()) => {
+                            info!(
+                                "Deleted SSL certificate {} for domain {} in account {}",
+                                certificate.certificate_id, self.name, certificate.account
+                            );
+                            PruneResult::deleted(
+                                &self.name,
+                                &certificate.account,
+                                &certificate.certificate_id,
+                                reason,
+                            )
+                        }
+                        Err(e) => PruneResult::failure(
+                            &self.name,
+                            &certificate.account,
+                            &certificate.certificate_id,
+                            reason,
+                            &e.to_string(),
+                        ),
+                    }
+                };
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    /// 从 SSL 服务商下载证书和私钥
+    pub async fn download_certificate(&self, certificate_id: &str) -> Result<CertBundle> {
+        let ssl_client = self.ssl_client()?;
+        let content = ssl_client.download(certificate_id).await?;
+        parse_cert_from_base64(&content)
+    }
+
     pub async fn check_ssl_status(&mut self) -> Result<Option<ApplyStatus>> {
         let ssl_client = self.ssl_client()?;
         if let Some(certificate_id) = &self.ssl_certificate_id() {
@@ -119,73 +535,231 @@ impl Domain {
         }
     }
 
-    pub async fn add_dns_record(&mut self, record: &str, sub_domain: &str) -> Result<u64> {
-        let dns_client = self.dns_client()?;
+    pub async fn add_dns_records(&mut self, challenges: &[DnsChallenge]) -> Result<Vec<u64>> {
+        let mut record_ids = Vec::with_capacity(challenges.len());
+        let mut records: Vec<(String, String)> = Vec::with_capacity(challenges.len());
+        let mut zones = None;
+        for challenge in challenges {
+            let (zone, sub_domain) = self
+                .challenge_record(&challenge.dns_key, &mut zones)
+                .await?;
+            // 同一子域名的多条验证记录（如通配符与根域名）需要并存，不能互相覆盖
+            let record = (zone, sub_domain);
+            let replace_existing = !records.contains(&record);
+            let record_id = self
+                .add_dns_record(&record.0, &challenge.dns_value, &record.1, replace_existing)
+                .await?;
+            records.push(record);
+            record_ids.push(record_id);
+        }
+        Ok(record_ids)
+    }
+
+    /// 验证记录所在的根域名及相对子域名，配置了 CNAME 委托时为委托目标。
+    /// `zones` 缓存账号托管的根域名，同一次申请的多条验证记录只查询一次
+    async fn challenge_record(
+        &self,
+        dns_key: &str,
+        zones: &mut Option<Vec<String>>,
+    ) -> Result<(String, String)> {
         let original_name = format!(".{}", self.original_name);
-        let sub_domain = sub_domain.replace(&original_name, "");
-        match dns_client.record_id(&self.original_name, &sub_domain).await {
-            Ok(record_id) => {
+        let Some(delegation) = &self.cname_delegation else {
+            return Ok((
+                self.original_name.clone(),
+                dns_key.replace(&original_name, ""),
+            ));
+        };
+
+        let name = if dns_key.ends_with(&original_name) {
+            dns_key.to_string()
+        } else {
+            format!("{}{}", dns_key, original_name)
+        };
+        let target = match &delegation.target {
+            Some(target) => target.trim_end_matches('.').to_lowercase(),
+            None => resolve_cname(&name).await?,
+        };
+        let zone = match &delegation.zone {
+            Some(zone) => zone.clone(),
+            None => delegated_zone(&target, self.dns_client()?.as_ref(), zones).await?,
+        };
+        let sub_domain = if target == zone {
+            "@".to_string()
+        } else {
+            target
+                .strip_suffix(&format!(".{}", zone))
+                .ok_or_else(|| {
+                    AppError::ConfigError(format!(
+                        "delegated name {} is not in zone {}",
+                        target, zone
+                    ))
+                })?
+                .to_string()
+        };
+        info!(
+            "Delegate DNS challenge {} to {} in zone {}",
+            name, target, zone
+        );
+        Ok((zone, sub_domain))
+    }
+
+    pub async fn add_dns_record(
+        &mut self,
+        zone: &str,
+        record: &str,
+        sub_domain: &str,
+        replace_existing: bool,
+    ) -> Result<u64> {
+        let dns_client = self.dns_client()?;
+        // 同一记录名的多个验证值需要同时存在，只有第一个值替换已有记录
+        let existing = if replace_existing {
+            match dns_client.record_id(zone, sub_domain).await {
+                Ok(record_id) => Some(record_id),
+                Err(e) => {
+                    debug!("no existing dns record: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let record_id = match existing {
+            Some(record_id) => {
                 debug!("old dns record id:{}", record_id);
-                let _ = self
-                    .modify_dns_record(record, record_id, &sub_domain)
-                    .await?;
-                self.set_dns_info(DnsInfo {
-                    dns_status: 1,
-                    dns_record_id: Some(record_id),
-                });
-                Ok(record_id)
+                self.modify_dns_record(zone, record, record_id, sub_domain)
+                    .await?
             }
-            Err(e) => {
-                debug!("add new record:{}", e);
-                let record_id = dns_client
-                    .add_record(record, &self.original_name, &sub_domain)
-                    .await?;
+            None => dns_client.add_record(record, zone, sub_domain).await?,
+        };
 
-                self.set_dns_info(DnsInfo {
-                    dns_status: 1,
-                    dns_record_id: Some(record_id),
-                });
-                Ok(record_id)
-            }
-        }
+        let mut info = self.dns_info.take().unwrap_or(DnsInfo {
+            dns_status: 1,
+            dns_records: Vec::new(),
+        });
+        info.dns_status = 1;
+        info.dns_records.push(DnsRecord {
+            record_id,
+            zone: zone.to_string(),
+            sub_domain: sub_domain.to_string(),
+        });
+        self.set_dns_info(info);
+        Ok(record_id)
     }
 
     pub async fn modify_dns_record(
         &self,
+        zone: &str,
         record: &str,
         record_id: u64,
         sub_domain: &str,
     ) -> Result<u64> {
         let dns_client = self.dns_client()?;
         let record_id = dns_client
-            .modify_record(record, record_id, &self.original_name, sub_domain)
+            .modify_record(record, record_id, zone, sub_domain)
             .await?;
         Ok(record_id)
     }
 
-    pub async fn delete_dns_record(&self) -> Result<String> {
-        let dns_client = self.dns_client()?;
-        if let Some(info) = &self.dns_info
-            && let Some(record_id) = info.dns_record_id
-        {
-            let request_id = dns_client
-                .delete_record(record_id, &self.original_name)
-                .await?;
-            return Ok(request_id);
+    pub async fn delete_dns_records(&self) -> Result<Vec<String>> {
+        let mut request_ids = Vec::new();
+        if let Some(info) = &self.dns_info {
+            let dns_client = self.dns_client()?;
+            for record in &info.dns_records {
+                let zone = if record.zone.is_empty() {
+                    &self.original_name
+                } else {
+                    &record.zone
+                };
+                let request_id = dns_client
+                    .delete_record(record.record_id, zone, &record.sub_domain)
+                    .await?;
+                request_ids.push(request_id);
+            }
         }
-        Ok("ok".to_string())
+        Ok(request_ids)
     }
 }
 
-pub async fn auto_update_ssl(mut domain: Domain) -> Result<()> {
-    if domain.ssl_certificate_id().is_none() {
-        domain.apply_ssl("DNS").await?;
+/// 委托目标所在的根域名，`zones` 为空时查询 dns_provider 托管的根域名并缓存
+async fn delegated_zone(
+    target: &str,
+    dns_client: &dyn DNS,
+    zones: &mut Option<Vec<String>>,
+) -> Result<String> {
+    let zones = match zones {
+        Some(zones) => zones,
+        None => zones.insert(dns_client.list_zones().await?),
+    };
+    find_zone(target, zones).cloned().ok_or_else(|| {
+        AppError::ConfigError(format!(
+            "delegated name {} is not hosted by dns_provider",
+            target
+        ))
+    })
+}
+
+/// 申请并部署证书，`force` 为 true 时不复用账号中已签发的证书
+pub async fn auto_update_ssl(
+    mut domain: Domain,
+    store: Store,
+    force: bool,
+) -> Result<Vec<DeployResult>> {
+    let mut state = store.state(&domain.name)?;
+    if domain.ssl_certificate_id().is_none()
+        && let Some(certificate_id) = &state.certificate_id
+    {
+        info!(
+            "Resume pending SSL certificate for domain {}: {}",
+            domain.name, certificate_id
+        );
+        domain.set_ssl_info(ApplyStatus {
+            certificate_id: certificate_id.clone(),
+            dns_challenges: Vec::new(),
+            file_challenges: Vec::new(),
+            status: 0,
+            can_download: false,
+        });
+    }
+
+    if domain.ssl_certificate_id().is_none() && force {
+        domain.apply_ssl(domain.validation_method()).await?;
+    } else if domain.ssl_certificate_id().is_none() {
+        match domain.find_reusable_certificate(&store).await {
+            Ok(Some(certificate_id)) => {
+                info!(
+                    "Reuse issued SSL certificate for domain {}: {}",
+                    domain.name, certificate_id
+                );
+                domain.set_ssl_info(ApplyStatus {
+                    certificate_id,
+                    dns_challenges: Vec::new(),
+                    file_challenges: Vec::new(),
+                    status: 1,
+                    can_download: true,
+                });
+            }
+            Ok(None) => domain.apply_ssl(domain.validation_method()).await?,
+            Err(e) => {
+                info!(
+                    "Failed to search issued SSL certificates for domain {}: {}",
+                    domain.name, e
+                );
+                domain.apply_ssl(domain.validation_method()).await?;
+            }
+        }
     }
 
     debug!("Applied SSL certificate for domain: {:?}", domain);
-    if let Some(mut certificate_id) = domain.ssl_certificate_id() {
+    if let Some(certificate_id) = domain.ssl_certificate_id() {
+        if state.certificate_id.as_ref() != Some(&certificate_id) {
+            state.certificate_id = Some(certificate_id.clone());
+            state.deployed.clear();
+            store.save_state(&domain.name, &state)?;
+        }
+
         let ssl_client = domain.ssl_client()?;
-        let cdn_client = domain.cdn_client()?;
+        let publisher = domain.file_validation.as_ref().map(crate::file_publisher);
+        let mut published = Vec::new();
         loop {
             let result = ssl_client.check_status(&certificate_id).await?;
             info!(
@@ -194,42 +768,378 @@ pub async fn auto_update_ssl(mut domain: Domain) -> Result<()> {
             );
             debug!("ApplyStatus: {:?}", result);
             if result.can_download {
-                if !domain.can_direct_update_ssl() {
-                    let content = ssl_client.download(&certificate_id).await?;
-                    let cert = parse_cert_from_base64(&content)?;
-                    let other_ssl_client = crate::ssl_client(
-                        &domain.cdn_provider.name,
-                        &domain.cdn_provider.secret_id,
-                        &domain.cdn_provider.secret_key,
-                    )?;
-                    certificate_id = other_ssl_client
-                        .upload(&cert.public_key, &cert.private_key)
-                        .await?;
+                // 文件验证没有添加 DNS 记录，也可能没有配置 dns_provider
+                match &publisher {
+                    Some(publisher) => {
+                        cleanup_file_challenges(publisher.as_ref(), &published).await
+                    }
+                    None => {
+                        let _ = domain.delete_dns_records().await?;
+                    }
                 }
-                let result = cdn_client
-                    .update_ssl(&domain.name(), &certificate_id)
-                    .await?;
-                let _ = domain.delete_dns_record().await?;
-                info!(
-                    "Update SSL certificate for domain {} success: {}",
-                    domain.name(),
-                    result
-                );
-                return Ok(());
+                let bundle = domain.download_certificate(&certificate_id).await?;
+                store.save_certificate(&domain.name, &certificate_id, &bundle.full_chain())?;
+                let cert = IssuedCertificate {
+                    source: Some(CertificateSource {
+                        certificate_id: certificate_id.clone(),
+                        provider: domain.ssl_provider.clone(),
+                    }),
+                    names: domain.names(),
+                    bundle,
+                };
+                return deploy_certificate(&domain, &cert, Some((&store, &mut state))).await;
             }
-            if domain.dns_status() == 0 || domain.dns_info.is_none() {
-                let add_dns_record = domain
-                    .add_dns_record(&result.dns_value, &result.dns_key)
-                    .await?;
+            if let Some(publisher) = &publisher {
+                if published.is_empty() && !result.file_challenges.is_empty() {
+                    for challenge in &result.file_challenges {
+                        publisher.publish(challenge).await?;
+                        published.push(challenge.clone());
+                    }
+                }
+            } else if domain.dns_status() == 0 || domain.dns_info.is_none() {
+                let record_ids = domain.add_dns_records(&result.dns_challenges).await?;
                 info!(
-                    "Added DNS record for domain {}: record id {}",
+                    "Added DNS records for domain {}: record ids {:?}",
                     domain.name(),
-                    add_dns_record
+                    record_ids
                 );
             }
-            info!("sleep 6 minutes for wait dns record verify");
+            info!("sleep 6 minutes for wait domain validation");
             sleep(Duration::from_mins(6)).await;
         }
     }
-    Ok(())
+    Ok(Vec::new())
+}
+
+/// 删除已发布的验证文件，失败时只记录日志，不影响证书部署
+async fn cleanup_file_challenges(publisher: &dyn Publisher, challenges: &[FileChallenge]) {
+    for challenge in challenges {
+        if let Err(e) = publisher.cleanup(challenge).await {
+            warn!(
+                "Failed to clean up validation file {} for domain {}: {}",
+                challenge.path, challenge.domain, e
+            );
+        }
+    }
+}
+
+/// 依次部署到每个目标；传入 progress 时已成功的目标记录在 state 中，下次运行时跳过
+pub async fn deploy_certificate(
+    domain: &Domain,
+    cert: &IssuedCertificate,
+    mut progress: Option<(&Store, &mut DomainState)>,
+) -> Result<Vec<DeployResult>> {
+    let mut results = Vec::new();
+    for target in domain.deploy_targets() {
+        let key = target.key();
+        if let Some((_, state)) = &progress
+            && state.deployed.contains(&key)
+        {
+            info!("Skip deployed target {} for domain {}", key, domain.name());
+            continue;
+        }
+
+        let target_results = match crate::deploy_client(&target, domain) {
+            Ok(deploy_client) => deploy_client.deploy(&domain.name, cert).await,
+            Err(e) => Err(e),
+        };
+        let target_results = match target_results {
+            Ok(target_results) => target_results,
+            Err(e) => vec![DeployResult::failure(
+                &domain.name,
+                target.kind(),
+                "-",
+                &e.to_string(),
+            )],
+        };
+        if let Some((store, state)) = &mut progress
+            && target_results.iter().all(|result| result.success)
+        {
+            state.deployed.push(key);
+            store.save_state(&domain.name, state)?;
+        }
+        results.extend(target_results);
+    }
+
+    if let Some((store, _)) = &progress
+        && results.iter().all(|result| result.success)
+    {
+        if let Some(source) = &cert.source {
+            store.set_current_certificate(&domain.name, &source.certificate_id)?;
+        }
+        store.clear_state(&domain.name)?;
+        if let Some(keep) = domain.prune_keep {
+            match domain.prune_certificates(store, keep, false).await {
+                Ok(prune_results) => results.extend(
+                    prune_results
+                        .into_iter()
+                        .map(PruneResult::into_deploy_result),
+                ),
+                Err(e) => warn!(
+                    "Failed to prune certificates for domain {}: {}",
+                    domain.name, e
+                ),
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// 选出待清理的证书及原因：只考虑域名与配置完全一致的证书，按到期时间保留最新的 `keep` 个，
+/// 已过期或已被替换的证书在未绑定 CDN 且不是当前证书时清理
+fn prune_candidates(
+    certificates: Vec<CertificateSummary>,
+    names: &[String],
+    keep: usize,
+    current_certificate_id: Option<&str>,
+) -> Vec<(CertificateSummary, &'static str)> {
+    let mut certificates: Vec<CertificateSummary> = certificates
+        .into_iter()
+        .filter(|certificate| same_names(&certificate.domains, names))
+        .filter(|certificate| {
+            certificate.issued || certificate.days_remaining().is_some_and(|days| days < 0)
+        })
+        .collect();
+    certificates.sort_by_key(|certificate| Reverse(certificate.expires_at));
+
+    certificates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, certificate)| {
+            let expired = certificate.days_remaining().is_some_and(|days| days < 0);
+            if (!expired && index < keep)
+                || !certificate.cdn_domains.is_empty()
+                || current_certificate_id == Some(certificate.certificate_id.as_str())
+            {
+                return None;
+            }
+            let reason = if expired { "已过期" } else { "已被替换" };
+            Some((certificate, reason))
+        })
+        .collect()
+}
+
+/// 证书域名与配置的域名完全一致（不区分大小写和顺序）
+fn same_names(certificate_names: &[String], names: &[String]) -> bool {
+    let normalize = |names: &[String]| {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+    normalize(certificate_names) == normalize(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn test_prune_candidates() {
+        let now = chrono::Utc::now();
+        let certificate = |id: &str, days: i64, cdn_domains: &[&str]| CertificateSummary {
+            certificate_id: id.to_string(),
+            domains: vec!["example.com".to_string()],
+            issued: days > 0,
+            expires_at: Some(now + chrono::Duration::days(days)),
+            cdn_domains: cdn_domains
+                .iter()
+                .map(|domain| domain.to_string())
+                .collect(),
+            ..Default::default()
+        };
+        let mut other = certificate("other", 10, &[]);
+        other.domains = vec!["www.example.com".to_string()];
+        let certificates = vec![
+            certificate("newest", 90, &[]),
+            certificate("bound", 60, &["example.com"]),
+            certificate("current", 45, &[]),
+            certificate("replaced", 30, &[]),
+            certificate("expired", -1, &[]),
+            other,
+        ];
+        let candidates = prune_candidates(
+            certificates,
+            &["example.com".to_string()],
+            1,
+            Some("current"),
+        );
+        let candidates: Vec<(&str, &str)> = candidates
+            .iter()
+            .map(|(certificate, reason)| (certificate.certificate_id.as_str(), *reason))
+            .collect();
+        assert_eq!(
+            candidates,
+            vec![("replaced", "已被替换"), ("expired", "已过期")]
+        );
+    }
+
+    #[test]
+    fn test_account() {
+        let mut provider = CloudProvider {
+            name: "tencent".to_string(),
+            secret_id: "AKIDabcdefgh12345678".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(provider.account(), "tencent:AKID****5678");
+        provider.profile = Some("prod".to_string());
+        assert_eq!(provider.account(), "tencent:prod");
+    }
+
+    #[tokio::test]
+    async fn test_validate_providers() {
+        let domain = |ssl_provider: Value, dns_provider: Value| -> Domain {
+            serde_json::from_value(json!({
+                "name": "example.com",
+                "ssl_provider": ssl_provider,
+                "dns_provider": dns_provider,
+                "cdn_provider": null,
+            }))
+            .unwrap()
+        };
+        let tencent = json!({ "name": "tencent", "secret_id": "id", "secret_key": "key" });
+        assert!(
+            domain(tencent.clone(), json!({ "name": "rfc2136" }))
+                .validate()
+                .is_ok()
+        );
+        assert!(domain(tencent.clone(), json!({})).validate().is_err());
+        // 只有文件验证可以省略 dns_provider
+        let mut file_validation = domain(tencent.clone(), Value::Null);
+        assert!(file_validation.validate().is_err());
+        file_validation.file_validation = Some(FileValidation::Http(
+            serde_json::from_value(json!({})).unwrap(),
+        ));
+        assert!(file_validation.validate().is_ok());
+        assert!(
+            file_validation
+                .delete_dns_records()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            domain(tencent.clone(), json!({ "name": "tencent" }))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            domain(
+                tencent.clone(),
+                json!({ "name": "tencent", "profile": "dns" })
+            )
+            .validate()
+            .is_ok()
+        );
+        assert!(
+            domain(json!({ "name": "acme" }), json!({ "name": "hook" }))
+                .validate()
+                .is_err()
+        );
+        // ACM 只能导入证书，只能作为 CDN 部署目标
+        let aws = json!({ "name": "aws", "secret_id": "id", "secret_key": "key" });
+        assert!(
+            domain(aws.clone(), json!({ "name": "hook" }))
+                .validate()
+                .is_err()
+        );
+        let mut cloudfront = domain(tencent.clone(), json!({ "name": "hook" }));
+        let mut target = aws;
+        target["type"] = json!("cdn");
+        cloudfront.targets = vec![serde_json::from_value(target).unwrap()];
+        assert!(cloudfront.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_sans() {
+        let domain = |name: &str, sans: &[&str], provider: &str| -> Domain {
+            serde_json::from_value(json!({
+                "name": name,
+                "sans": sans,
+                "ssl_provider": { "name": provider, "secret_id": "id", "secret_key": "key" },
+                "dns_provider": { "name": "hook" },
+                "cdn_provider": null,
+            }))
+            .unwrap()
+        };
+        assert!(domain("example.com", &[], "tencent").validate().is_ok());
+        assert!(
+            domain("example.com", &["www.example.com"], "tencent")
+                .validate()
+                .is_err()
+        );
+        assert!(domain("*.example.com", &[], "tencent").validate().is_err());
+        let mut local_key = domain("example.com", &[], "tencent");
+        local_key.local_key = Some(KeyAlgorithm::EcdsaP256);
+        assert!(local_key.validate().is_err());
+        // 华为云 SCM 只能导入证书
+        assert!(domain("example.com", &[], "huawei").validate().is_err());
+    }
+
+    #[test]
+    fn test_dns_info_legacy_record_ids() {
+        let info: DnsInfo = serde_json::from_value(json!({
+            "dns_status": 1,
+            "dns_record_ids": [11, 12],
+        }))
+        .unwrap();
+        let ids: Vec<u64> = info.dns_records.iter().map(|r| r.record_id).collect();
+        assert_eq!(ids, vec![11, 12]);
+        assert!(info.dns_records.iter().all(|r| r.zone.is_empty()));
+
+        let info: DnsInfo = serde_json::from_value(json!({
+            "dns_status": 1,
+            "dns_records": [{ "record_id": 13, "zone": "example.net" }],
+        }))
+        .unwrap();
+        assert_eq!(info.dns_records[0].zone, "example.net");
+    }
+
+    #[tokio::test]
+    async fn test_delegated_zone_lists_zones_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct CountingDns(AtomicUsize);
+
+        #[async_trait::async_trait]
+        impl DNS for CountingDns {
+            async fn add_record(&self, _: &str, _: &str, _: &str) -> Result<u64> {
+                Err(AppError::CloudError("unexpected call".to_string()))
+            }
+            async fn modify_record(&self, _: &str, _: u64, _: &str, _: &str) -> Result<u64> {
+                Err(AppError::CloudError("unexpected call".to_string()))
+            }
+            async fn delete_record(&self, _: u64, _: &str, _: &str) -> Result<String> {
+                Err(AppError::CloudError("unexpected call".to_string()))
+            }
+            async fn record_id(&self, _: &str, _: &str) -> Result<u64> {
+                Err(AppError::CloudError("unexpected call".to_string()))
+            }
+            async fn list_zones(&self) -> Result<Vec<String>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(vec![
+                    "example.net".to_string(),
+                    "acme.example.net".to_string(),
+                ])
+            }
+        }
+
+        let dns = CountingDns(AtomicUsize::new(0));
+        let mut zones = None;
+        let zone = delegated_zone("a.acme.example.net", &dns, &mut zones)
+            .await
+            .unwrap();
+        assert_eq!(zone, "acme.example.net");
+        let zone = delegated_zone("b.example.net", &dns, &mut zones)
+            .await
+            .unwrap();
+        assert_eq!(zone, "example.net");
+        assert!(
+            delegated_zone("c.example.org", &dns, &mut zones)
+                .await
+                .is_err()
+        );
+        assert_eq!(dns.0.load(Ordering::SeqCst), 1);
+    }
 }
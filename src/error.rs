@@ -17,6 +17,9 @@ pub enum AppError {
     #[error("JSON Error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("SSH Error: {0}")]
+    SshError(String),
+
     #[error("Io Error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -71,3 +74,21 @@ impl From<base64::DecodeError> for AppError {
         AppError::Other(err.to_string())
     }
 }
+
+impl From<ssh2::Error> for AppError {
+    fn from(err: ssh2::Error) -> Self {
+        AppError::SshError(err.to_string())
+    }
+}
+
+impl From<kube::Error> for AppError {
+    fn from(err: kube::Error) -> Self {
+        AppError::CloudError(err.to_string())
+    }
+}
+
+impl From<openssl::error::ErrorStack> for AppError {
+    fn from(err: openssl::error::ErrorStack) -> Self {
+        AppError::Other(err.to_string())
+    }
+}
@@ -0,0 +1,209 @@
+use crate::Result;
+use crate::digest::{hmac_sha256, sha256_hex, to_hex, uri_encode};
+use crate::domain::CloudProvider;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, Url};
+use serde_json::Value;
+use tracing::debug;
+
+const ALGORITHM: &str = "SDK-HMAC-SHA256";
+const CONTENT_TYPE: &str = "application/json";
+
+/// 调用华为云接口，`secret_id` 为 Access Key（AK），`secret_key` 为 Secret Access Key（SK）
+pub struct HuaweiClient {
+    client: Client,
+    access_key: String,
+    secret_key: String,
+}
+
+impl HuaweiClient {
+    pub fn new(provider: &CloudProvider) -> Self {
+        HuaweiClient {
+            client: Client::new(),
+            access_key: provider.secret_id.clone(),
+            secret_key: provider.secret_key.clone(),
+        }
+    }
+
+    /// 发送 JSON 请求，响应为空时返回 `Value::Null`
+    pub async fn request(&self, method: Method, url: Url, body: Option<&Value>) -> Result<Value> {
+        let body = body.map(Value::to_string).unwrap_or_default();
+        let now = Utc::now();
+        let sdk_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = self.authorization(method.as_str(), &url, body.as_bytes(), now)?;
+
+        debug!("Huawei Cloud request: {} {}", method, url);
+        let response = self
+            .client
+            .request(method, url.clone())
+            .header("content-type", CONTENT_TYPE)
+            .header("x-sdk-date", sdk_date)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        let value: Value = if body.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&body).unwrap_or_else(|_| Value::String(body.clone()))
+        };
+        if !status.is_success() {
+            return Err(AppError::CloudError(format!(
+                "huawei cloud {} failed: HTTP {} {}",
+                url,
+                status,
+                error_message(&value).unwrap_or(body)
+            )));
+        }
+        Ok(value)
+    }
+
+    /// 计算 `authorization` 请求头，参与签名的请求头为 content-type、host 和 x-sdk-date
+    fn authorization(
+        &self,
+        method: &str,
+        url: &Url,
+        payload: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<String> {
+        let sdk_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(format!("invalid url: {}", url).into()),
+        };
+        let headers = [
+            ("content-type", CONTENT_TYPE),
+            ("host", host.as_str()),
+            ("x-sdk-date", sdk_date.as_str()),
+        ];
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = canonical_request(method, url, &headers, payload)?;
+        let string_to_sign = format!(
+            "{}\n{}\n{}",
+            ALGORITHM,
+            sdk_date,
+            sha256_hex(canonical_request.as_bytes())?
+        );
+        let signature = to_hex(&hmac_sha256(
+            self.secret_key.as_bytes(),
+            string_to_sign.as_bytes(),
+        )?);
+        Ok(format!(
+            "{} Access={}, SignedHeaders={}, Signature={}",
+            ALGORITHM, self.access_key, signed_headers, signature
+        ))
+    }
+}
+
+/// 规范请求，与 AWS SigV4 的区别是路径必须以 `/` 结尾。`headers` 已按名称排序
+fn canonical_request(
+    method: &str,
+    url: &Url,
+    headers: &[(&str, &str)],
+    payload: &[u8],
+) -> Result<String> {
+    let mut path = url.path().to_string();
+    if !path.ends_with('/') {
+        path.push('/');
+    }
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| (uri_encode(&name), uri_encode(&value)))
+        .collect();
+    query.sort();
+    let canonical_query = query
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    Ok(format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        path,
+        canonical_query,
+        canonical_headers,
+        signed_headers,
+        sha256_hex(payload)?
+    ))
+}
+
+/// 各服务的错误格式不同：`error_msg`、`message` 或嵌套在 `error` 中
+fn error_message(value: &Value) -> Option<String> {
+    let error = value
+        .get("error")
+        .filter(|error| error.is_object())
+        .unwrap_or(value);
+    ["error_msg", "message"]
+        .iter()
+        .find_map(|key| error[*key].as_str())
+        .map(
+            |message| match error["error_code"].as_str().or(error["code"].as_str()) {
+                Some(code) => format!("{} {}", code, message),
+                None => message.to_string(),
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 签名值由独立实现按华为云 APIG 签名规则计算
+    #[test]
+    fn test_authorization() {
+        let client = HuaweiClient::new(&CloudProvider {
+            name: "huawei".to_string(),
+            secret_id: "HPUAEXAMPLE".to_string(),
+            secret_key: "SKEXAMPLE".to_string(),
+            ..Default::default()
+        });
+        let url = Url::parse("https://dns.myhuaweicloud.com/v2/zones?type=public&name=example.com")
+            .unwrap();
+        let now = DateTime::from_timestamp(1700000000, 0).unwrap();
+        assert_eq!(
+            client.authorization("GET", &url, b"", now).unwrap(),
+            "SDK-HMAC-SHA256 Access=HPUAEXAMPLE, SignedHeaders=content-type;host;x-sdk-date, \
+             Signature=46fa4baf39f473f3dadf0a57ddf5491b15df040cd64c2dec7aae18c6c7d2cb22"
+        );
+    }
+
+    #[test]
+    fn test_canonical_request() {
+        let url =
+            Url::parse("https://dns.myhuaweicloud.com/v2/zones?type=public&name=a b").unwrap();
+        let headers = [
+            ("content-type", CONTENT_TYPE),
+            ("host", "dns.myhuaweicloud.com"),
+            ("x-sdk-date", "20240101T000000Z"),
+        ];
+        assert_eq!(
+            canonical_request("GET", &url, &headers, b"").unwrap(),
+            "GET\n/v2/zones/\nname=a%20b&type=public\n\
+             content-type:application/json\nhost:dns.myhuaweicloud.com\nx-sdk-date:20240101T000000Z\n\n\
+             content-type;host;x-sdk-date\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            error_message(&json!({"error": {"error_code": "CDN.0001", "error_msg": "bad"}})),
+            Some("CDN.0001 bad".to_string())
+        );
+    }
+}
@@ -1,15 +1,31 @@
+pub mod aws;
 pub mod cdn;
 pub mod cli;
+pub mod deploy;
+pub mod digest;
 pub mod dns;
 pub mod domain;
 pub mod error;
+pub mod huawei;
+pub mod profile;
+pub mod report;
+pub mod sigv4;
 pub mod ssl;
+pub mod store;
+pub mod tencent;
+pub mod validation;
+pub mod zone;
 
 pub type Result<T> = std::result::Result<T, error::AppError>;
 
-use crate::cdn::{CDN, TencentCDN};
-use crate::dns::{DNS, TencentDNS};
-use crate::ssl::{SSL, TencentSSL};
+use crate::cdn::{CDN, CloudFrontCDN, HuaweiCDN, TencentCDN};
+use crate::deploy::{CdnDeploy, Deploy, DeployTarget, KubernetesDeploy, SshDeploy};
+use crate::dns::{DNS, HookDNS, HuaweiDNS, Rfc2136DNS, Route53DNS, TencentDNS};
+use crate::error::AppError;
+use crate::ssl::{AcmSSL, HuaweiSSL, SSL, TencentSSL};
+use crate::validation::{
+    BucketPublisher, FileValidation, HttpPublisher, Publisher, WebrootPublisher,
+};
 use futures::StreamExt;
 use reqwest::{Client, StatusCode, Url};
 use std::sync::Arc;
@@ -17,7 +33,7 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::info;
 
-use crate::domain::Domain;
+use crate::domain::{CloudProvider, Domain};
 
 pub async fn parse_domains(client: &Client, domains: Vec<Domain>) -> Option<Vec<Domain>> {
     let domains: Vec<Domain> = domains
@@ -83,32 +99,71 @@ async fn is_domain_valid(client: &Client, domain: &str) -> bool {
     }
 }
 
-pub fn ssl_client(provider: &str, secret_id: &str, secret_key: &str) -> Result<Arc<dyn SSL>> {
-    match provider {
+pub fn ssl_client(provider: &CloudProvider) -> Result<Arc<dyn SSL>> {
+    match provider.name.as_str() {
         "tencent" => {
-            let ssl_client = TencentSSL::new(secret_id, secret_key)?;
+            let ssl_client = TencentSSL::new(&provider.secret_id, &provider.secret_key)?;
             Ok(Arc::new(ssl_client))
         }
-        _ => panic!("invalid ssl cloud provider"),
+        "aws" => Ok(Arc::new(AcmSSL::new(provider)?)),
+        "huawei" => Ok(Arc::new(HuaweiSSL::new(provider)?)),
+        _ => Err(AppError::ConfigError(format!(
+            "invalid ssl cloud provider: {}",
+            provider.name
+        ))),
     }
 }
 
-pub fn dns_client(provider: &str, secret_id: &str, secret_key: &str) -> Result<Arc<dyn DNS>> {
-    match provider {
+pub fn dns_client(provider: &CloudProvider) -> Result<Arc<dyn DNS>> {
+    match provider.name.as_str() {
         "tencent" => {
-            let dns_client = TencentDNS::new(secret_id, secret_key)?;
+            let dns_client = TencentDNS::new(&provider.secret_id, &provider.secret_key)?;
             Ok(Arc::new(dns_client))
         }
-        _ => panic!("invalid dns cloud provider"),
+        "hook" => Ok(Arc::new(HookDNS::new(provider)?)),
+        "rfc2136" => Ok(Arc::new(Rfc2136DNS::new(provider)?)),
+        "aws" => Ok(Arc::new(Route53DNS::new(provider)?)),
+        "huawei" => Ok(Arc::new(HuaweiDNS::new(provider)?)),
+        _ => Err(AppError::ConfigError(format!(
+            "invalid dns cloud provider: {}",
+            provider.name
+        ))),
     }
 }
 
-pub fn cdn_client(provider: &str, secret_id: &str, secret_key: &str) -> Result<Arc<dyn CDN>> {
-    match provider {
+pub fn cdn_client(provider: &CloudProvider) -> Result<Arc<dyn CDN>> {
+    match provider.name.as_str() {
         "tencent" => {
-            let cdn_client = TencentCDN::new(secret_id, secret_key)?;
+            let cdn_client = TencentCDN::new(&provider.secret_id, &provider.secret_key)?;
             Ok(Arc::new(cdn_client))
         }
-        _ => panic!("invalid cdn cloud provider"),
+        "aws" => Ok(Arc::new(CloudFrontCDN::new(provider)?)),
+        "huawei" => Ok(Arc::new(HuaweiCDN::new(provider)?)),
+        _ => Err(AppError::ConfigError(format!(
+            "invalid cdn cloud provider: {}",
+            provider.name
+        ))),
+    }
+}
+
+/// 创建部署客户端，部署到 CDN 时使用域名配置中的 `verify` 和 `https` 选项
+pub fn deploy_client(target: &DeployTarget, domain: &Domain) -> Result<Arc<dyn Deploy>> {
+    match target {
+        DeployTarget::Cdn(provider) => Ok(Arc::new(CdnDeploy::new(
+            provider.clone(),
+            domain.verify.clone(),
+            domain.https.clone(),
+        ))),
+        DeployTarget::Ssh(target) => Ok(Arc::new(SshDeploy::new(target.clone()))),
+        DeployTarget::Kubernetes(target) => Ok(Arc::new(KubernetesDeploy::new(target.clone()))),
+    }
+}
+
+/// 创建文件验证的发布客户端
+pub fn file_publisher(validation: &FileValidation) -> Arc<dyn Publisher> {
+    match validation {
+        FileValidation::Webroot(target) => Arc::new(WebrootPublisher::new(target.clone())),
+        FileValidation::Bucket(target) => Arc::new(BucketPublisher::new(target.clone())),
+        FileValidation::Http(options) => Arc::new(HttpPublisher::new(options.clone())),
     }
 }
@@ -2,11 +2,18 @@
 
 use clap::Parser;
 use csu::Result;
-use csu::cli::args::{Cli, Commands};
-use csu::cli::command::{check_ssl_remin_days, update_ssl_certificate};
+use csu::cli::args::{CertsCommands, Cli, Commands, OutputFormat};
+use csu::cli::command::{
+    check_ssl_remin_days, discover_domains, export_certificate, import_certificate,
+    list_certificates, merge_domains, prune_certificates, revoke_certificate,
+    update_ssl_certificate,
+};
 use csu::domain::Domain;
 use csu::error::AppError;
+use csu::profile::Profiles;
+use csu::report::DeployResult;
 use csu::ssl::CertificateInfo;
+use csu::store::Store;
 use reqwest::Client;
 use std::fs::File;
 use std::io::BufReader;
@@ -22,6 +29,29 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
+    let profiles = match &cli.profiles {
+        Some(path) => Profiles::load(path)?,
+        None => Profiles::for_domains_file(&cli.domains)?,
+    };
+
+    // 自动发现时域名配置文件可以不存在
+    if let Commands::Discover {
+        cdn,
+        dns,
+        ssl,
+        write,
+    } = &cli.command
+    {
+        let discovered = discover_domains(&profiles, cdn, dns, ssl.as_deref()).await?;
+        if *write {
+            let added = merge_domains(&cli.domains, discovered)?;
+            println!("Added {} domains to {}", added, cli.domains);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&discovered)?);
+        }
+        return Ok(());
+    }
+
     if !Path::new(&cli.domains).exists() {
         error!("Domains file does not exist: {}", cli.domains);
         process::exit(1);
@@ -30,7 +60,7 @@ async fn main() -> Result<()> {
     let file = File::open(&cli.domains)?;
     let reader = BufReader::new(file);
 
-    let domains: Vec<Domain> = match serde_json::from_reader(reader) {
+    let mut domains: Vec<Domain> = match serde_json::from_reader(reader) {
         Ok(value) => value,
         Err(e) => {
             error!("Failed to load domains: {}", e);
@@ -38,19 +68,19 @@ async fn main() -> Result<()> {
         }
     };
 
-    let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()?;
+    profiles.resolve_domains(&mut domains)?;
+    for domain in &domains {
+        domain.validate()?;
+    }
 
-    let valid_domains = match csu::parse_domains(&client, domains.clone()).await {
-        Some(domains) => domains,
-        None => {
-            error!("No valid domains found in file: {}", cli.domains);
-            process::exit(1);
-        }
+    let store = match &cli.store {
+        Some(path) => Store::new(path),
+        None => Store::for_domains_file(&cli.domains),
     };
 
-    debug!("Valid domains: {:?}", valid_domains);
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
 
     match cli.command {
         Commands::Check => {
@@ -58,6 +88,7 @@ async fn main() -> Result<()> {
                 "Checking SSL certificate status for domains: {}",
                 cli.domains
             );
+            let valid_domains = valid_domains(&client, domains, &cli.domains).await;
             let info = check_ssl_remin_days(valid_domains).await?;
             let info: Vec<CertificateInfo> = info
                 .into_iter()
@@ -69,26 +100,124 @@ async fn main() -> Result<()> {
         }
         Commands::Update => {
             info!("Updating SSL certificates for domains: {}", cli.domains);
+            let valid_domains = valid_domains(&client, domains, &cli.domains).await;
             let info = check_ssl_remin_days(valid_domains).await?;
             let domains: Vec<Domain> = info
                 .into_iter()
-                .filter(|domain| match &domain.certificate_info {
-                    Some(info) => info.need_update(),
-                    None => true,
+                .filter(|domain| {
+                    let need_update = match &domain.certificate_info {
+                        Some(info) => info.need_update(),
+                        None => true,
+                    };
+                    // 上次运行部分目标部署失败的域名需要继续部署
+                    need_update
+                        || store
+                            .state(&domain.name)
+                            .map(|state| state.is_pending())
+                            .unwrap_or(false)
                 })
                 .collect();
-            update_ssl_certificate(domains).await?;
+            let results = update_ssl_certificate(domains, &store, false).await?;
+            print_deploy_results(&results);
         }
         Commands::ForceUpdate => {
             info!(
                 "Force updating SSL certificates for domains: {}",
                 cli.domains
             );
-            update_ssl_certificate(domains).await?;
+            let results = update_ssl_certificate(domains, &store, true).await?;
+            print_deploy_results(&results);
+        }
+        Commands::Export {
+            domain,
+            output,
+            keystore,
+            certificate_id,
+        } => {
+            let domain = find_domain(&domains, &domain);
+            let paths =
+                export_certificate(&domain, &store, certificate_id, &output, keystore).await?;
+            for path in paths {
+                println!("{}", path.display());
+            }
+        }
+        Commands::Import { domain, cert, key } => {
+            let domain = find_domain(&domains, &domain);
+            let results = import_certificate(&domain, &cert, &key).await?;
+            print_deploy_results(&results);
+        }
+        Commands::Revoke {
+            target,
+            reason,
+            replace,
+        } => {
+            let results = revoke_certificate(&domains, &store, &target, &reason, replace).await?;
+            print_deploy_results(&results);
         }
+        Commands::Certs {
+            command: CertsCommands::List { format },
+        } => {
+            let certificates = list_certificates(&domains).await?;
+            match format {
+                OutputFormat::Table => {
+                    let table = Table::new(&certificates).to_string();
+                    println!("=== 证书列表 ===");
+                    println!("{}", table);
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&certificates)?);
+                }
+            }
+        }
+        Commands::Certs {
+            command:
+                CertsCommands::Prune {
+                    domain,
+                    keep,
+                    dry_run,
+                },
+        } => {
+            let domains = match domain {
+                Some(domain) => vec![find_domain(&domains, &domain)],
+                None => domains,
+            };
+            let results = prune_certificates(&domains, &store, keep, dry_run).await?;
+            let table = Table::new(&results).to_string();
+            println!("=== 证书清理 ===");
+            println!("{}", table);
+        }
+        Commands::Discover { .. } => unreachable!(),
         Commands::Version => {
             println!("CDN SSL Auto Updater version 2.1.0");
         }
     }
     Ok(())
 }
+
+async fn valid_domains(client: &Client, domains: Vec<Domain>, domains_file: &str) -> Vec<Domain> {
+    let valid_domains = match csu::parse_domains(client, domains).await {
+        Some(domains) => domains,
+        None => {
+            error!("No valid domains found in file: {}", domains_file);
+            process::exit(1);
+        }
+    };
+    debug!("Valid domains: {:?}", valid_domains);
+    valid_domains
+}
+
+fn find_domain(domains: &[Domain], name: &str) -> Domain {
+    match domains.iter().find(|domain| domain.name == name) {
+        Some(domain) => domain.clone(),
+        None => {
+            error!("Domain not found in domains file: {}", name);
+            process::exit(1);
+        }
+    }
+}
+
+fn print_deploy_results(results: &[DeployResult]) {
+    let table = Table::new(results).to_string();
+    println!("=== 部署结果 ===");
+    println!("{}", table);
+}
@@ -0,0 +1,134 @@
+use crate::Result;
+use crate::deploy::DeployTarget;
+use crate::domain::{CloudProvider, Domain};
+use crate::error::AppError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// 凭证配置文件，域名配置中以 `{"profile": "名称"}` 引用其中的云服务商凭证
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Profiles {
+    profiles: BTreeMap<String, CloudProvider>,
+}
+
+impl Profiles {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// 默认读取域名配置文件同级的 `profiles.json`，不存在时为空
+    pub fn for_domains_file(domains_file: &str) -> Result<Self> {
+        let path = Path::new(domains_file)
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("profiles.json");
+        if !path.exists() {
+            return Ok(Profiles::default());
+        }
+        Profiles::load(path)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    /// 服务商在 `supported` 中的 profile 名称
+    pub fn names_for(&self, supported: &[&str]) -> Vec<String> {
+        self.profiles
+            .iter()
+            .filter(|(_, provider)| supported.contains(&provider.name.as_str()))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// 按名称取得凭证，服务商不在 `supported` 中时报错，`usage` 为报错中显示的用途
+    pub fn get_for(&self, name: &str, usage: &str, supported: &[&str]) -> Result<CloudProvider> {
+        let provider = self.get(name)?;
+        if !supported.contains(&provider.name.as_str()) {
+            return Err(AppError::ConfigError(format!(
+                "profile {}: {} cannot be used as {}, expected one of {}",
+                name,
+                provider.name,
+                usage,
+                supported.join(", ")
+            )));
+        }
+        Ok(provider)
+    }
+
+    /// 按名称取得凭证，返回的凭证保留对该 profile 的引用
+    pub fn get(&self, name: &str) -> Result<CloudProvider> {
+        let provider = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| AppError::ConfigError(format!("profile not found: {}", name)))?;
+        Ok(CloudProvider {
+            profile: Some(name.to_string()),
+            ..provider.clone()
+        })
+    }
+
+    /// 将引用 profile 的服务商替换为实际凭证
+    pub fn resolve(&self, provider: &mut CloudProvider) -> Result<()> {
+        if let Some(name) = &provider.profile {
+            *provider = self.get(name)?;
+        }
+        Ok(())
+    }
+
+    pub fn resolve_domains(&self, domains: &mut [Domain]) -> Result<()> {
+        for domain in domains {
+            self.resolve(&mut domain.ssl_provider)?;
+            if let Some(provider) = &mut domain.dns_provider {
+                self.resolve(provider)?;
+            }
+            if let Some(provider) = &mut domain.cdn_provider {
+                self.resolve(provider)?;
+            }
+            for target in &mut domain.targets {
+                if let DeployTarget::Cdn(provider) = target {
+                    self.resolve(provider)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_names_for() {
+        let profiles: Profiles = serde_json::from_value(json!({
+            "hook": { "name": "hook" },
+            "prod": { "name": "tencent", "secret_id": "id", "secret_key": "key" },
+            "route53": { "name": "aws", "secret_id": "id", "secret_key": "key" },
+        }))
+        .unwrap();
+        assert_eq!(
+            profiles.names_for(&["tencent", "aws"]),
+            vec!["prod", "route53"]
+        );
+        assert!(
+            profiles
+                .get_for("hook", "cdn_provider", &["tencent"])
+                .is_err()
+        );
+        assert_eq!(
+            profiles
+                .get_for("prod", "cdn_provider", &["tencent"])
+                .unwrap()
+                .profile
+                .as_deref(),
+            Some("prod")
+        );
+    }
+}
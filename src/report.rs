@@ -0,0 +1,147 @@
+use std::borrow::Cow::{self, Borrowed};
+use tabled::Tabled;
+
+#[derive(Debug, Clone)]
+pub struct DeployResult {
+    pub domain: String,
+    pub target: String,
+    pub host: String,
+    pub success: bool,
+    pub message: String,
+}
+
+impl DeployResult {
+    pub fn success(domain: &str, target: &str, host: &str, message: &str) -> Self {
+        DeployResult {
+            domain: domain.to_string(),
+            target: target.to_string(),
+            host: host.to_string(),
+            success: true,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn failure(domain: &str, target: &str, host: &str, message: &str) -> Self {
+        DeployResult {
+            domain: domain.to_string(),
+            target: target.to_string(),
+            host: host.to_string(),
+            success: false,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Tabled for DeployResult {
+    const LENGTH: usize = 5;
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Borrowed("域名"),
+            Borrowed("部署目标"),
+            Borrowed("主机"),
+            Borrowed("结果"),
+            Borrowed("信息"),
+        ]
+    }
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let status = if self.success { "成功" } else { "失败" };
+        vec![
+            self.domain.as_str().into(),
+            self.target.as_str().into(),
+            self.host.as_str().into(),
+            status.into(),
+            self.message.as_str().into(),
+        ]
+    }
+}
+
+/// 清理证书的结果，`deleted` 为 false 且 `message` 为空表示试运行
+#[derive(Debug, Clone)]
+pub struct PruneResult {
+    pub domain: String,
+    pub account: String,
+    pub certificate_id: String,
+    pub reason: String,
+    pub deleted: bool,
+    pub message: String,
+}
+
+impl PruneResult {
+    pub fn pending(domain: &str, account: &str, certificate_id: &str, reason: &str) -> Self {
+        PruneResult {
+            domain: domain.to_string(),
+            account: account.to_string(),
+            certificate_id: certificate_id.to_string(),
+            reason: reason.to_string(),
+            deleted: false,
+            message: String::new(),
+        }
+    }
+
+    pub fn deleted(domain: &str, account: &str, certificate_id: &str, reason: &str) -> Self {
+        PruneResult {
+            deleted: true,
+            ..PruneResult::pending(domain, account, certificate_id, reason)
+        }
+    }
+
+    pub fn failure(
+        domain: &str,
+        account: &str,
+        certificate_id: &str,
+        reason: &str,
+        message: &str,
+    ) -> Self {
+        PruneResult {
+            message: message.to_string(),
+            ..PruneResult::pending(domain, account, certificate_id, reason)
+        }
+    }
+
+    /// 部署后自动清理时，结果与部署结果一起输出
+    pub fn into_deploy_result(self) -> DeployResult {
+        let message = if self.deleted {
+            format!("{} {}", self.reason, self.certificate_id)
+        } else {
+            format!("{} {}: {}", self.reason, self.certificate_id, self.message)
+        };
+        DeployResult {
+            domain: self.domain,
+            target: "prune".to_string(),
+            host: self.account,
+            success: self.deleted,
+            message,
+        }
+    }
+}
+
+impl Tabled for PruneResult {
+    const LENGTH: usize = 6;
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Borrowed("域名"),
+            Borrowed("账号"),
+            Borrowed("证书 ID"),
+            Borrowed("原因"),
+            Borrowed("结果"),
+            Borrowed("信息"),
+        ]
+    }
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let status = if self.deleted {
+            "已删除"
+        } else if self.message.is_empty() {
+            "待删除"
+        } else {
+            "失败"
+        };
+        vec![
+            self.domain.as_str().into(),
+            self.account.as_str().into(),
+            self.certificate_id.as_str().into(),
+            self.reason.as_str().into(),
+            status.into(),
+            self.message.as_str().into(),
+        ]
+    }
+}
@@ -0,0 +1,162 @@
+use super::{ApplyStatus, CertificateSummary, RevokeStatus, SSL, unsupported};
+use crate::Result;
+use crate::aws::AwsClient;
+use crate::domain::{CloudProvider, Endpoint};
+use crate::error::AppError;
+use base64::{Engine as _, engine::general_purpose};
+use chrono::DateTime;
+use reqwest::Method;
+use serde_json::{Value, json};
+
+const DEFAULT_ENDPOINT: &str = "https://acm.us-east-1.amazonaws.com/";
+const LIST_CERTIFICATES_LIMIT: u64 = 1000;
+/// ListCertificates 默认只返回 RSA 2048 证书
+const KEY_TYPES: [&str; 7] = [
+    "RSA_1024",
+    "RSA_2048",
+    "RSA_3072",
+    "RSA_4096",
+    "EC_prime256v1",
+    "EC_secp384r1",
+    "EC_secp521r1",
+];
+
+/// AWS Certificate Manager，只用于导入证书供 CloudFront 使用，证书固定导入 us-east-1 区域。
+/// `options.acm_endpoint` 可覆盖接口地址
+pub struct AcmSSL {
+    client: AwsClient,
+    endpoint: Endpoint,
+}
+
+impl AcmSSL {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        Ok(AcmSSL {
+            client: AwsClient::new(provider),
+            endpoint: provider.endpoint("acm_endpoint", DEFAULT_ENDPOINT)?,
+        })
+    }
+
+    async fn call(&self, action: &str, body: Value) -> Result<Value> {
+        let target = format!("CertificateManager.{}", action);
+        let response = self
+            .client
+            .request(
+                "acm",
+                Method::POST,
+                self.endpoint.url("/")?,
+                &[
+                    ("content-type", "application/x-amz-json-1.1"),
+                    ("x-amz-target", &target),
+                ],
+                body.to_string(),
+            )
+            .await?;
+        if response.body.is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_str(&response.body)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl SSL for AcmSSL {
+    async fn apply(&self, _domains: &[String], _dv_auth_method: &str) -> Result<String> {
+        Err(unsupported("aws acm", "applying certificates"))
+    }
+
+    async fn download(&self, _certificate_id: &str) -> Result<String> {
+        Err(unsupported("aws acm", "downloading certificates"))
+    }
+
+    async fn check_status(&self, _certificate_id: &str) -> Result<ApplyStatus> {
+        Err(unsupported("aws acm", "applying certificates"))
+    }
+
+    /// 完整证书链中第一张为站点证书，其余作为证书链导入
+    async fn upload(&self, certificate_public_key: &str, private_key: &str) -> Result<String> {
+        const END: &str = "-----END CERTIFICATE-----";
+        let split = certificate_public_key
+            .find(END)
+            .map(|index| index + END.len())
+            .ok_or_else(|| AppError::Other("invalid certificate pem".to_string()))?;
+        let (certificate, chain) = certificate_public_key.split_at(split);
+        let mut body = json!({
+            "Certificate": general_purpose::STANDARD.encode(certificate.trim()),
+            "PrivateKey": general_purpose::STANDARD.encode(private_key.trim()),
+        });
+        if !chain.trim().is_empty() {
+            body["CertificateChain"] = json!(general_purpose::STANDARD.encode(chain.trim()));
+        }
+        let response = self.call("ImportCertificate", body).await?;
+        response["CertificateArn"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AppError::CloudError("aws acm import certificate failed".to_string()))
+    }
+
+    async fn list(&self) -> Result<Vec<CertificateSummary>> {
+        let mut certificates = Vec::new();
+        let mut next_token: Option<String> = None;
+        loop {
+            let mut body = json!({
+                "Includes": { "keyTypes": KEY_TYPES },
+                "MaxItems": LIST_CERTIFICATES_LIMIT,
+            });
+            if let Some(next_token) = &next_token {
+                body["NextToken"] = json!(next_token);
+            }
+            let response = self.call("ListCertificates", body).await?;
+            for certificate in response["CertificateSummaryList"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+            {
+                let Some(certificate_arn) = certificate["CertificateArn"].as_str() else {
+                    continue;
+                };
+                let mut domains: Vec<String> = certificate["SubjectAlternativeNameSummaries"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|name| name.as_str().map(str::to_string))
+                    .collect();
+                if let Some(domain) = certificate["DomainName"].as_str()
+                    && !domains.iter().any(|name| name == domain)
+                {
+                    domains.insert(0, domain.to_string());
+                }
+                let status = certificate["Status"].as_str().unwrap_or_default();
+                certificates.push(CertificateSummary {
+                    certificate_id: certificate_arn.to_string(),
+                    domains,
+                    issued: status == "ISSUED",
+                    status: status.to_string(),
+                    expires_at: certificate["NotAfter"]
+                        .as_f64()
+                        .and_then(|time| DateTime::from_timestamp(time as i64, 0)),
+                    ..Default::default()
+                });
+            }
+            next_token = response["NextToken"].as_str().map(str::to_string);
+            if next_token.is_none() {
+                break;
+            }
+        }
+        Ok(certificates)
+    }
+
+    async fn revoke(&self, _certificate_id: &str, _reason: &str) -> Result<RevokeStatus> {
+        Err(unsupported("aws acm", "revoking certificates"))
+    }
+
+    /// 证书仍被 CloudFront 使用时 ACM 会返回 ResourceInUseException
+    async fn delete(&self, certificate_id: &str) -> Result<()> {
+        self.call(
+            "DeleteCertificate",
+            json!({ "CertificateArn": certificate_id }),
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -2,9 +2,15 @@ use chrono::{DateTime, Utc};
 use native_tls::TlsConnector;
 use serde::Deserialize;
 use std::borrow::Cow::{self, Borrowed};
-use std::net::TcpStream;
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
 use tabled::Tabled;
 
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 证书剩余有效天数不超过该值时需要更新
+pub const RENEW_BEFORE_DAYS: i64 = 3;
+
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct CertificateInfo {
     pub domain: String,
@@ -12,6 +18,9 @@ pub struct CertificateInfo {
     pub valid_from: DateTime<Utc>,
     pub valid_to: DateTime<Utc>,
     pub days_remaining: i64,
+    /// 证书序列号，十六进制，以冒号分隔
+    #[serde(default)]
+    pub serial: String,
 }
 
 impl Tabled for CertificateInfo {
@@ -44,16 +53,41 @@ impl CertificateInfo {
     }
 
     pub fn need_update(&self) -> bool {
-        self.days_remaining <= 3
+        self.days_remaining <= RENEW_BEFORE_DAYS
     }
 }
 
 pub fn check_ssl_certificate(domain: &str) -> crate::Result<CertificateInfo> {
+    check_ssl_certificate_at(domain, None)
+}
+
+/// 连接指定地址（如 CDN 边缘节点 IP）并以 `domain` 作为 SNI 获取证书，`address` 为空时解析域名
+pub fn check_ssl_certificate_at(
+    domain: &str,
+    address: Option<&str>,
+) -> crate::Result<CertificateInfo> {
     // 尝试使用HTTPS
     let connector = TlsConnector::new()?;
 
     // 连接到服务器
-    let stream = TcpStream::connect(format!("{}:443", domain))?;
+    let address = address.unwrap_or(domain);
+    let socket_addr = match address.parse::<IpAddr>() {
+        Ok(ip) => SocketAddr::new(ip, 443),
+        Err(_) => {
+            let address = if address.contains(':') {
+                address.to_string()
+            } else {
+                format!("{}:443", address)
+            };
+            address
+                .to_socket_addrs()?
+                .next()
+                .ok_or("无法解析服务器地址")?
+        }
+    };
+    let stream = TcpStream::connect_timeout(&socket_addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
     let tls_stream = connector.connect(domain, stream)?;
 
     // 获取证书
@@ -81,5 +115,14 @@ pub fn check_ssl_certificate(domain: &str) -> crate::Result<CertificateInfo> {
         valid_from,
         valid_to,
         days_remaining,
+        serial: cert.raw_serial_as_string(),
     })
 }
+
+/// PEM 格式证书的序列号，格式与 `CertificateInfo::serial` 一致
+pub fn certificate_serial(pem: &str) -> crate::Result<String> {
+    let der = openssl::x509::X509::from_pem(pem.as_bytes())?.to_der()?;
+    let parsed_cert =
+        x509_parser::parse_x509_certificate(&der).map_err(|e| format!("解析证书失败: {}", e))?;
+    Ok(parsed_cert.1.raw_serial_as_string())
+}
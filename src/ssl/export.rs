@@ -0,0 +1,287 @@
+use super::CertBundle;
+use crate::Result;
+use crate::error::AppError;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::PKey;
+use openssl::rand::rand_bytes;
+use openssl::sha::{Sha1, sha1};
+use openssl::stack::Stack;
+use openssl::x509::X509;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// PKCS#12 和 JKS 文件的密码
+pub const EXPORT_PASSWORD_ENV: &str = "CSU_EXPORT_PASSWORD";
+
+/// JKS 私钥保护算法（sun.security.provider.KeyProtector）的 OID 1.3.6.1.4.1.42.2.17.1.1
+const JKS_KEY_PROTECTOR_OID: [u8; 10] =
+    [0x2b, 0x06, 0x01, 0x04, 0x01, 0x2a, 0x02, 0x11, 0x01, 0x01];
+
+/// 写出 PEM 文件：证书、中间证书、完整证书链和私钥，返回写出的文件路径
+pub fn write_pem_files(bundle: &CertBundle, output: &Path, name: &str) -> Result<Vec<PathBuf>> {
+    let files = [
+        (format!("{}.crt", name), bundle.leaf.clone(), false),
+        (
+            format!("{}.chain.crt", name),
+            bundle.intermediates.concat(),
+            false,
+        ),
+        (
+            format!("{}.fullchain.crt", name),
+            bundle.full_chain(),
+            false,
+        ),
+        (format!("{}.key", name), bundle.key.to_string(), true),
+    ];
+
+    let mut paths = Vec::with_capacity(files.len());
+    for (file_name, content, secret) in files {
+        let content = Zeroizing::new(content);
+        let path = output.join(file_name);
+        write_file(&path, content.as_bytes(), secret)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// 写出文件，`secret` 为 true 时权限为 0600，已存在的文件也会在写入内容前收紧权限
+pub fn write_file(path: &Path, content: &[u8], secret: bool) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if secret {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    if secret {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = secret;
+    file.write_all(content)?;
+    Ok(())
+}
+
+/// 读取 PKCS#12 和 JKS 文件的密码，不通过命令行参数传入，避免出现在进程列表中
+pub fn export_password() -> Result<Zeroizing<String>> {
+    match std::env::var(EXPORT_PASSWORD_ENV) {
+        Ok(password) if !password.is_empty() => Ok(Zeroizing::new(password)),
+        _ => Err(AppError::ConfigError(format!(
+            "{} is required to write pkcs12 and jks files",
+            EXPORT_PASSWORD_ENV
+        ))),
+    }
+}
+
+pub fn build_pkcs12(bundle: &CertBundle, alias: &str, password: &str) -> Result<Vec<u8>> {
+    let key = PKey::private_key_from_pem(bundle.key.as_bytes())?;
+    let leaf = X509::from_pem(bundle.leaf.as_bytes())?;
+    let mut chain = Stack::new()?;
+    for intermediate in &bundle.intermediates {
+        chain.push(X509::from_pem(intermediate.as_bytes())?)?;
+    }
+    let pkcs12 = Pkcs12::builder()
+        .name(alias)
+        .pkey(&key)
+        .cert(&leaf)
+        .ca(chain)
+        .build2(password)?;
+    Ok(pkcs12.to_der()?)
+}
+
+/// 生成包含一个私钥条目的 Java KeyStore（JKS）
+pub fn build_jks(bundle: &CertBundle, alias: &str, password: &str) -> Result<Vec<u8>> {
+    if password.is_empty() {
+        return Err(AppError::ConfigError(
+            "jks password is required".to_string(),
+        ));
+    }
+    let password: Zeroizing<Vec<u8>> = Zeroizing::new(
+        password
+            .encode_utf16()
+            .flat_map(|c| c.to_be_bytes())
+            .collect(),
+    );
+
+    let key = PKey::private_key_from_pem(bundle.key.as_bytes())?;
+    let plain_key = Zeroizing::new(key.private_key_to_pkcs8()?);
+    let protected_key = protect_jks_key(&plain_key, &password)?;
+
+    let mut certificates = vec![X509::from_pem(bundle.leaf.as_bytes())?.to_der()?];
+    for intermediate in &bundle.intermediates {
+        certificates.push(X509::from_pem(intermediate.as_bytes())?.to_der()?);
+    }
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&0xFEED_FEEDu32.to_be_bytes());
+    data.extend_from_slice(&2u32.to_be_bytes());
+    data.extend_from_slice(&1u32.to_be_bytes());
+
+    // 私钥条目
+    data.extend_from_slice(&1u32.to_be_bytes());
+    write_jks_utf(&mut data, alias)?;
+    let timestamp = chrono::Utc::now().timestamp_millis() as u64;
+    data.extend_from_slice(&timestamp.to_be_bytes());
+    write_jks_bytes(&mut data, &protected_key)?;
+    data.extend_from_slice(&(certificates.len() as u32).to_be_bytes());
+    for certificate in &certificates {
+        write_jks_utf(&mut data, "X.509")?;
+        write_jks_bytes(&mut data, certificate)?;
+    }
+
+    let mut digest = Sha1::new();
+    digest.update(&password);
+    digest.update(b"Mighty Aphrodite");
+    digest.update(&data);
+    data.extend_from_slice(&digest.finish());
+    Ok(data)
+}
+
+/// 按 sun.security.provider.KeyProtector 加密 PKCS#8 私钥，返回 EncryptedPrivateKeyInfo DER
+fn protect_jks_key(plain_key: &[u8], password: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0u8; 20];
+    rand_bytes(&mut salt)?;
+
+    let mut encrypted = Vec::with_capacity(20 + plain_key.len() + 20);
+    encrypted.extend_from_slice(&salt);
+    let mut digest = salt;
+    for chunk in plain_key.chunks(20) {
+        let mut sha = Sha1::new();
+        sha.update(password);
+        sha.update(&digest);
+        digest = sha.finish();
+        encrypted.extend(chunk.iter().zip(digest.iter()).map(|(a, b)| a ^ b));
+    }
+    let mut check = Zeroizing::new(password.to_vec());
+    check.extend_from_slice(plain_key);
+    encrypted.extend_from_slice(&sha1(&check));
+
+    let mut algorithm = der_tlv(0x06, &JKS_KEY_PROTECTOR_OID);
+    algorithm.extend_from_slice(&[0x05, 0x00]);
+    let mut info = der_tlv(0x30, &algorithm);
+    info.extend(der_tlv(0x04, &encrypted));
+    Ok(der_tlv(0x30, &info))
+}
+
+fn der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut data = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        data.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        data.push(0x80 | bytes.len() as u8);
+        data.extend(bytes);
+    }
+    data.extend_from_slice(content);
+    data
+}
+
+fn write_jks_utf(data: &mut Vec<u8>, value: &str) -> Result<()> {
+    let len = u16::try_from(value.len())
+        .map_err(|_| AppError::Other(format!("jks string too long: {}", value)))?;
+    data.extend_from_slice(&len.to_be_bytes());
+    data.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn write_jks_bytes(data: &mut Vec<u8>, value: &[u8]) -> Result<()> {
+    let len = u32::try_from(value.len())
+        .map_err(|_| AppError::Other("jks entry too large".to_string()))?;
+    data.extend_from_slice(&len.to_be_bytes());
+    data.extend_from_slice(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssl::{KeyAlgorithm, generate_private_key, parse_cert_from_pem};
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::x509::{X509Builder, X509NameBuilder};
+
+    #[test]
+    fn test_build_jks_integrity_and_key_protection() {
+        let key = generate_private_key(KeyAlgorithm::EcdsaP256).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "cdn.example.com").unwrap();
+        let name = name.build();
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(90).unwrap())
+            .unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        let certificate = builder.build();
+        let bundle = parse_cert_from_pem(
+            &certificate.to_pem().unwrap(),
+            &key.private_key_to_pem_pkcs8().unwrap(),
+        )
+        .unwrap();
+
+        let jks = build_jks(&bundle, "cdn.example.com", "changeit").unwrap();
+        assert_eq!(&jks[..4], &[0xFE, 0xED, 0xFE, 0xED]);
+
+        let password: Vec<u8> = "changeit"
+            .encode_utf16()
+            .flat_map(|c| c.to_be_bytes())
+            .collect();
+        let (body, digest) = jks.split_at(jks.len() - 20);
+        let mut sha = Sha1::new();
+        sha.update(&password);
+        sha.update(b"Mighty Aphrodite");
+        sha.update(body);
+        assert_eq!(digest, sha.finish());
+
+        let plain_key = key.private_key_to_pkcs8().unwrap();
+        let protected = protect_jks_key(&plain_key, &password).unwrap();
+        let encrypted = &protected[protected.len() - (20 + plain_key.len() + 20)..];
+        let (salt, rest) = encrypted.split_at(20);
+        let (cipher, check) = rest.split_at(plain_key.len());
+        let mut digest = salt.to_vec();
+        let mut decrypted = Vec::new();
+        for chunk in cipher.chunks(20) {
+            let mut sha = Sha1::new();
+            sha.update(&password);
+            sha.update(&digest);
+            digest = sha.finish().to_vec();
+            decrypted.extend(chunk.iter().zip(digest.iter()).map(|(a, b)| a ^ b));
+        }
+        assert_eq!(decrypted, plain_key);
+        assert_eq!(check, sha1(&[password.as_slice(), &plain_key].concat()));
+    }
+
+    /// 覆盖已存在的文件时同样收紧为 0600
+    #[cfg(unix)]
+    #[test]
+    fn test_write_secret_file_tightens_existing_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("csu-secret-{}.key", std::process::id()));
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        write_file(&path, b"new", true).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode, 0o600);
+    }
+}
@@ -0,0 +1,133 @@
+use super::{ApplyStatus, CertificateSummary, RevokeStatus, SSL, unsupported};
+use crate::Result;
+use crate::domain::{CloudProvider, Endpoint};
+use crate::error::AppError;
+use crate::huawei::HuaweiClient;
+use chrono::{NaiveDateTime, Utc};
+use reqwest::Method;
+use serde_json::json;
+
+const DEFAULT_ENDPOINT: &str = "https://scm.cn-north-4.myhuaweicloud.com";
+const LIST_CERTIFICATES_LIMIT: usize = 50;
+
+/// 华为云云证书管理服务（CCM/SCM），只用于导入证书供华为云 CDN 使用。
+/// `options.scm_endpoint` 可覆盖接口地址
+pub struct HuaweiSSL {
+    client: HuaweiClient,
+    endpoint: Endpoint,
+}
+
+impl HuaweiSSL {
+    pub fn new(provider: &CloudProvider) -> Result<Self> {
+        Ok(HuaweiSSL {
+            client: HuaweiClient::new(provider),
+            endpoint: provider.endpoint("scm_endpoint", DEFAULT_ENDPOINT)?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl SSL for HuaweiSSL {
+    async fn apply(&self, _domains: &[String], _dv_auth_method: &str) -> Result<String> {
+        Err(unsupported("huawei cloud scm", "applying certificates"))
+    }
+
+    async fn download(&self, _certificate_id: &str) -> Result<String> {
+        Err(unsupported("huawei cloud scm", "downloading certificates"))
+    }
+
+    async fn check_status(&self, _certificate_id: &str) -> Result<ApplyStatus> {
+        Err(unsupported("huawei cloud scm", "applying certificates"))
+    }
+
+    /// 证书名称在账号内唯一，使用导入时间生成
+    async fn upload(&self, certificate_public_key: &str, private_key: &str) -> Result<String> {
+        let body = json!({
+            "name": format!("csu-{}", Utc::now().format("%Y%m%d%H%M%S%3f")),
+            "certificate": certificate_public_key,
+            "private_key": private_key,
+        });
+        let response = self
+            .client
+            .request(
+                Method::POST,
+                self.endpoint.url("/v3/scm/certificates/import")?,
+                Some(&body),
+            )
+            .await?;
+        response["certificate_id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| {
+                AppError::CloudError("huawei cloud scm import certificate failed".to_string())
+            })
+    }
+
+    async fn list(&self) -> Result<Vec<CertificateSummary>> {
+        let mut certificates = Vec::new();
+        let mut offset = 0;
+        loop {
+            let mut url = self.endpoint.url("/v3/scm/certificates")?;
+            url.query_pairs_mut()
+                .append_pair("limit", &LIST_CERTIFICATES_LIMIT.to_string())
+                .append_pair("offset", &offset.to_string());
+            let response = self.client.request(Method::GET, url, None).await?;
+            let page = response["certificates"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            for certificate in page {
+                let Some(certificate_id) = certificate["id"].as_str() else {
+                    continue;
+                };
+                let mut domains: Vec<String> = certificate["sans"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .split([',', ';'])
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                if let Some(domain) = certificate["domain"].as_str()
+                    && !domain.is_empty()
+                    && !domains.iter().any(|name| name == domain)
+                {
+                    domains.insert(0, domain.to_string());
+                }
+                let status = certificate["status"].as_str().unwrap_or_default();
+                certificates.push(CertificateSummary {
+                    certificate_id: certificate_id.to_string(),
+                    domains,
+                    // 导入的证书状态为 UPLOAD
+                    issued: status == "ISSUED" || status == "UPLOAD",
+                    status: status.to_string(),
+                    expires_at: certificate["expire_time"]
+                        .as_str()
+                        .and_then(|time| {
+                            NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%.f").ok()
+                        })
+                        .map(|time| time.and_utc()),
+                    ..Default::default()
+                });
+            }
+            offset += page.len();
+            let total = response["total_count"].as_u64().unwrap_or(0) as usize;
+            if page.len() < LIST_CERTIFICATES_LIMIT || offset >= total {
+                break;
+            }
+        }
+        Ok(certificates)
+    }
+
+    async fn revoke(&self, _certificate_id: &str, _reason: &str) -> Result<RevokeStatus> {
+        Err(unsupported("huawei cloud scm", "revoking certificates"))
+    }
+
+    async fn delete(&self, certificate_id: &str) -> Result<()> {
+        let url = self
+            .endpoint
+            .url(&format!("/v3/scm/certificates/{}", certificate_id))?;
+        self.client.request(Method::DELETE, url, None).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::borrow::Cow::{self, Borrowed};
+use tabled::Tabled;
+
+/// 服务商账号中已有的证书
+#[derive(Debug, Serialize, Default, Clone)]
+pub struct CertificateSummary {
+    /// 证书所在账号，格式为 `服务商:profile`，未使用 profile 时为 `服务商:` 加脱敏后的 secret_id（如 `tencent:AKID****abcd`）
+    pub account: String,
+    pub certificate_id: String,
+    pub domains: Vec<String>,
+    pub status: String,
+    /// 已签发可下载
+    pub issued: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 当前使用该证书的 CDN 域名
+    pub cdn_domains: Vec<String>,
+}
+
+impl Tabled for CertificateSummary {
+    const LENGTH: usize = 6;
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Borrowed("账号"),
+            Borrowed("证书 ID"),
+            Borrowed("域名"),
+            Borrowed("状态"),
+            Borrowed("到期时间"),
+            Borrowed("CDN 域名"),
+        ]
+    }
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let expires_at = self
+            .expires_at
+            .map(|expires_at| expires_at.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        vec![
+            self.account.as_str().into(),
+            self.certificate_id.as_str().into(),
+            self.domains.join("\n").into(),
+            self.status.as_str().into(),
+            expires_at.into(),
+            self.cdn_domains.join("\n").into(),
+        ]
+    }
+}
+
+impl CertificateSummary {
+    pub fn days_remaining(&self) -> Option<i64> {
+        self.expires_at
+            .map(|expires_at| (expires_at - Utc::now()).num_days())
+    }
+}
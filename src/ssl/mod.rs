@@ -1,27 +1,88 @@
+mod acm;
 mod check;
+mod export;
+mod huawei;
+mod inventory;
+mod key;
 mod tencent;
 mod utils;
 
-pub use check::{CertificateInfo, check_ssl_certificate};
+pub use acm::AcmSSL;
+pub use check::{
+    CertificateInfo, RENEW_BEFORE_DAYS, certificate_serial, check_ssl_certificate,
+    check_ssl_certificate_at,
+};
+pub use export::{
+    EXPORT_PASSWORD_ENV, build_jks, build_pkcs12, export_password, write_file, write_pem_files,
+};
+pub use huawei::HuaweiSSL;
+pub use inventory::CertificateSummary;
+pub use key::{KeyAlgorithm, generate_private_key};
 use serde::Deserialize;
 pub use tencent::TencentSSL;
-pub use utils::parse_cert_from_base64;
+pub(crate) use tencent::parse_tencent_time;
+pub use utils::{
+    CertBundle, name_covered, parse_cert_from_base64, parse_cert_from_pem, verify_certificate_names,
+};
 
 use crate::Result;
+use crate::error::AppError;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct ApplyStatus {
     pub certificate_id: String,
-    pub dns_key: String,
-    pub dns_value: String,
+    /// 每个待验证域名对应一条 DNS 验证记录
+    #[serde(default)]
+    pub dns_challenges: Vec<DnsChallenge>,
+    /// 文件验证（FILE）方式下需要发布的验证文件
+    #[serde(default)]
+    pub file_challenges: Vec<FileChallenge>,
     pub status: i32,
     pub can_download: bool,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct DnsChallenge {
+    pub domain: String,
+    pub dns_key: String,
+    pub dns_value: String,
+}
+
+/// 吊销请求的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevokeStatus {
+    Revoked,
+    /// 服务商要求先验证域名所有权，内容为需要添加的验证信息，完成验证前证书仍然有效
+    PendingValidation(Vec<String>),
+}
+
+/// 文件验证需要在 `http://<domain><path>` 返回 `content`
+#[derive(Debug, Deserialize, Clone)]
+pub struct FileChallenge {
+    pub domain: String,
+    /// 以 `/` 开头的完整路径，如 `/.well-known/pki-validation/fileauth.txt`
+    pub path: String,
+    pub content: String,
+}
+
 #[async_trait::async_trait]
 pub trait SSL: Send + Sync {
-    async fn apply(&self, domain: &str, dv_auth_method: &str) -> Result<String>;
+    async fn apply(&self, domains: &[String], dv_auth_method: &str) -> Result<String>;
     async fn download(&self, certificate_id: &str) -> Result<String>;
     async fn check_status(&self, certificate_id: &str) -> Result<ApplyStatus>;
     async fn upload(&self, certificate_public_key: &str, private_key: &str) -> Result<String>;
+    /// 列出账号中的全部证书，`account` 和 `cdn_domains` 由调用方填充
+    async fn list(&self) -> Result<Vec<CertificateSummary>>;
+    /// 吊销证书，`reason` 为吊销原因
+    async fn revoke(&self, certificate_id: &str, reason: &str) -> Result<RevokeStatus>;
+    /// 删除证书，证书仍绑定云资源时返回错误
+    async fn delete(&self, certificate_id: &str) -> Result<()>;
+}
+
+/// 只用于导入证书的服务商（如 AWS ACM、华为云 SCM）不支持的操作
+fn unsupported(service: &str, action: &str) -> AppError {
+    AppError::CloudError(format!(
+        "{} does not support {}, it can only be used as a deployment target",
+        service, action
+    ))
 }
@@ -1,17 +1,21 @@
-use super::{ApplyStatus, SSL};
+use super::{ApplyStatus, CertificateSummary, DnsChallenge, FileChallenge, RevokeStatus, SSL};
 use crate::Result;
 use crate::error::AppError;
+use crate::tencent::{Action, TencentClient};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use serde_json::{Value, json};
 use tencent_sdk::{
     client::TencentCloudAsync,
     core::TencentCloudResult,
     middleware::RetryAsync,
-    services::ssl::{ApplyCertificate, CheckCertificate, DownloadCertificate, UploadCertificate},
+    services::ssl::{ApplyCertificate, DownloadCertificate, UploadCertificate},
     transport::async_impl::ReqwestAsync,
 };
-use tracing::debug;
+use tracing::{debug, warn};
 
 pub struct TencentSSL {
     pub client: TencentCloudAsync<RetryAsync<ReqwestAsync>>,
+    api: TencentClient,
 }
 
 impl TencentSSL {
@@ -22,13 +26,17 @@ impl TencentSSL {
             .with_retry(3, std::time::Duration::from_millis(200))
             .build()?;
 
-        Ok(TencentSSL { client })
+        Ok(TencentSSL {
+            client,
+            api: TencentClient::new(secret_id, secret_key),
+        })
     }
 }
 
 #[async_trait::async_trait]
 impl SSL for TencentSSL {
-    async fn apply(&self, domain: &str, dv_auth_method: &str) -> Result<String> {
+    async fn apply(&self, domains: &[String], dv_auth_method: &str) -> Result<String> {
+        let domain = single_domain(domains)?;
         let request = ApplyCertificate::new(dv_auth_method, domain);
         let response = self.client.request(&request).await?;
         match response.response.certificate_id {
@@ -50,59 +58,23 @@ impl SSL for TencentSSL {
         }
     }
 
+    /// 证书状态和验证信息通过 DescribeCertificate 查询，DvAuthPath 不在固定版本的 SDK 中
     async fn check_status(&self, certificate_id: &str) -> Result<ApplyStatus> {
-        let request = CheckCertificate::new(certificate_id);
-        match self.client.request(&request).await {
-            Ok(response) => {
-                debug!("CheckCertificate response: {:?}", response);
-                if let Some(status) = response.response.status
-                    && status == 1
-                {
-                    return Ok(ApplyStatus {
-                        certificate_id: certificate_id.to_string(),
-                        dns_key: "".to_string(),
-                        dns_value: "".to_string(),
-                        status,
-                        can_download: true,
-                    });
-                }
-
-                if let Some(status) = response.response.status
-                    && status == 0
-                    && let Some(dv_auth) = response.response.dv_auth_detail
-                    && let Some(dv_auth_key) = dv_auth.dv_auth_key
-                    && let Some(dv_auth_value) = dv_auth.dv_auth_value
-                {
-                    return Ok(ApplyStatus {
-                        certificate_id: certificate_id.to_string(),
-                        dns_key: dv_auth_key,
-                        dns_value: dv_auth_value,
-                        status,
-                        can_download: false,
-                    });
-                }
-
-                if let Some(status) = response.response.status {
-                    Ok(ApplyStatus {
-                        certificate_id: certificate_id.to_string(),
-                        dns_key: "".to_string(),
-                        dns_value: "".to_string(),
-                        status,
-                        can_download: false,
-                    })
-                } else {
-                    Err(AppError::CloudError(
-                        "tencent cloud check ssl certificate status missing fields".to_string(),
-                    ))
-                }
-            }
-            Err(e) => {
-                return Err(AppError::CloudError(format!(
+        let response = self
+            .api
+            .request(
+                &action("DescribeCertificate"),
+                &json!({ "CertificateId": certificate_id }),
+            )
+            .await
+            .map_err(|e| {
+                AppError::CloudError(format!(
                     "tencent cloud check ssl certificate status failed: {}",
                     e
-                )));
-            }
-        }
+                ))
+            })?;
+        debug!("DescribeCertificate response: {:?}", response);
+        apply_status(certificate_id, &response)
     }
 
     async fn upload(&self, certificate_public_key: &str, private_key: &str) -> Result<String> {
@@ -117,4 +89,290 @@ impl SSL for TencentSSL {
             )),
         }
     }
+
+    async fn list(&self) -> Result<Vec<CertificateSummary>> {
+        let mut certificates = Vec::new();
+        let mut offset = 0;
+        loop {
+            let response = self
+                .api
+                .request(
+                    &action("DescribeCertificates"),
+                    &json!({ "Offset": offset, "Limit": DESCRIBE_CERTIFICATES_LIMIT }),
+                )
+                .await?;
+            let page = response["Certificates"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let total_count = response["TotalCount"].as_u64().unwrap_or_default();
+            offset += page.len() as u64;
+            certificates.extend(page.iter().filter_map(certificate_summary));
+
+            if page.is_empty() || offset >= total_count {
+                break;
+            }
+        }
+        debug!("DescribeCertificates total: {}", certificates.len());
+        Ok(certificates)
+    }
+
+    async fn revoke(&self, certificate_id: &str, reason: &str) -> Result<RevokeStatus> {
+        let response = self
+            .api
+            .request(
+                &action("RevokeCertificate"),
+                &json!({ "CertificateId": certificate_id, "Reason": reason }),
+            )
+            .await?;
+        let status = revoke_status(&response);
+        if let RevokeStatus::PendingValidation(auths) = &status {
+            warn!(
+                "Revoke certificate {} requires domain validation: {}",
+                certificate_id,
+                auths.join("; ")
+            );
+        }
+        Ok(status)
+    }
+
+    async fn delete(&self, certificate_id: &str) -> Result<()> {
+        // 由腾讯云检查证书是否仍绑定 CDN、CLB 等资源，已绑定时不删除
+        let response = self
+            .api
+            .request(
+                &action("DeleteCertificate"),
+                &json!({ "CertificateId": certificate_id, "IsCheckResource": true }),
+            )
+            .await?;
+        match response["DeleteResult"].as_bool() {
+            Some(true) => Ok(()),
+            _ => Err(AppError::CloudError(format!(
+                "tencent cloud delete ssl certificate {} failed, it may still be bound to cloud resources",
+                certificate_id
+            ))),
+        }
+    }
+}
+
+fn action(action: &str) -> Action<'_> {
+    Action {
+        service: "ssl",
+        version: SSL_API_VERSION,
+        action,
+    }
+}
+
+/// 部分证书吊销前需要再次验证域名所有权，此时返回 RevokeDomainValidateAuths，完成验证后才会吊销
+fn revoke_status(response: &Value) -> RevokeStatus {
+    let auths: Vec<String> = response["RevokeDomainValidateAuths"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|auth| {
+            let field = |name: &str| auth[name].as_str().unwrap_or_default().to_string();
+            let path = field("DomainValidateAuthPath");
+            if path.is_empty() {
+                format!(
+                    "domain {} TXT {} {}",
+                    field("DomainValidateAuthDomain"),
+                    field("DomainValidateAuthKey"),
+                    field("DomainValidateAuthValue")
+                )
+            } else {
+                format!(
+                    "domain {} file {}/{} {}",
+                    field("DomainValidateAuthDomain"),
+                    path.trim_end_matches('/'),
+                    field("DomainValidateAuthKey"),
+                    field("DomainValidateAuthValue")
+                )
+            }
+        })
+        .collect();
+    if auths.is_empty() {
+        RevokeStatus::Revoked
+    } else {
+        RevokeStatus::PendingValidation(auths)
+    }
+}
+
+/// DescribeCertificate 的 Status：1 为已签发，0 为审核中，此时 DvAuthDetail 中为验证信息
+fn apply_status(certificate_id: &str, response: &Value) -> Result<ApplyStatus> {
+    let status = response["Status"].as_i64().ok_or_else(|| {
+        AppError::CloudError(
+            "tencent cloud check ssl certificate status missing fields".to_string(),
+        )
+    })? as i32;
+    let mut dns_challenges = Vec::new();
+    let mut file_challenges = Vec::new();
+    if status == 0 {
+        let dv_auth = &response["DvAuthDetail"];
+        for auth in dv_auth["DvAuths"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            push_challenge(&mut dns_challenges, &mut file_challenges, auth);
+        }
+        if dns_challenges.is_empty() && file_challenges.is_empty() {
+            push_challenge(&mut dns_challenges, &mut file_challenges, dv_auth);
+        }
+    }
+    Ok(ApplyStatus {
+        certificate_id: certificate_id.to_string(),
+        dns_challenges,
+        file_challenges,
+        status,
+        can_download: status == 1,
+    })
+}
+
+fn certificate_summary(certificate: &Value) -> Option<CertificateSummary> {
+    let certificate_id = certificate["CertificateId"].as_str()?.to_string();
+    let mut domains: Vec<String> = certificate["SubjectAltName"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    if let Some(domain) = certificate["Domain"].as_str()
+        && !domains.iter().any(|name| name == domain)
+    {
+        domains.insert(0, domain.to_string());
+    }
+    let status = certificate["Status"].as_u64();
+    Some(CertificateSummary {
+        certificate_id,
+        domains,
+        issued: status == Some(1),
+        status: certificate["StatusName"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| status.map(|status| status.to_string()))
+            .unwrap_or_default(),
+        expires_at: certificate["CertEndTime"]
+            .as_str()
+            .and_then(parse_tencent_time),
+        ..Default::default()
+    })
+}
+
+const SSL_API_VERSION: &str = "2019-12-05";
+/// DescribeCertificates 单页最多返回 1000 条
+const DESCRIBE_CERTIFICATES_LIMIT: u64 = 1000;
+
+/// 文件验证时 DvAuthPath 为验证文件所在目录，DvAuthKey 为文件名；没有 DvAuthPath 时为 DNS 验证
+fn push_challenge(
+    dns_challenges: &mut Vec<DnsChallenge>,
+    file_challenges: &mut Vec<FileChallenge>,
+    auth: &Value,
+) {
+    let (Some(key), Some(value)) = (auth["DvAuthKey"].as_str(), auth["DvAuthValue"].as_str())
+    else {
+        return;
+    };
+    let domain = auth["DvAuthDomain"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    match auth["DvAuthPath"].as_str().filter(|path| !path.is_empty()) {
+        Some(path) => file_challenges.push(FileChallenge {
+            domain,
+            path: format!(
+                "/{}/{}",
+                path.trim_matches('/'),
+                key.trim_start_matches('/')
+            ),
+            content: value.to_string(),
+        }),
+        None => dns_challenges.push(DnsChallenge {
+            domain,
+            dns_key: key.to_string(),
+            dns_value: value.to_string(),
+        }),
+    }
+}
+
+/// 腾讯云免费证书仅支持单个域名，不支持通配符
+fn single_domain(domains: &[String]) -> Result<&str> {
+    match domains {
+        [domain] if !domain.starts_with("*.") => Ok(domain),
+        _ => Err(AppError::CloudError(format!(
+            "tencent cloud free certificate only supports a single non-wildcard domain: {}",
+            domains.join(",")
+        ))),
+    }
+}
+
+/// 腾讯云返回的时间为北京时间，格式 `2025-01-01 08:00:00`
+pub(crate) fn parse_tencent_time(time: &str) -> Option<DateTime<Utc>> {
+    let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").ok()?;
+    let offset = FixedOffset::east_opt(8 * 3600)?;
+    time.and_local_timezone(offset)
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_status() {
+        let response = json!({
+            "Status": 0,
+            "DvAuthDetail": {
+                "DvAuths": [
+                    {
+                        "DvAuthDomain": "example.com",
+                        "DvAuthPath": "/.well-known/pki-validation/",
+                        "DvAuthKey": "fileauth.txt",
+                        "DvAuthValue": "content"
+                    },
+                    {
+                        "DvAuthDomain": "www.example.com",
+                        "DvAuthKey": "_dnsauth.www",
+                        "DvAuthValue": "value"
+                    }
+                ]
+            }
+        });
+        let status = apply_status("cert-1", &response).unwrap();
+        assert!(!status.can_download);
+        assert_eq!(
+            status.file_challenges[0].path,
+            "/.well-known/pki-validation/fileauth.txt"
+        );
+        assert_eq!(status.dns_challenges[0].dns_key, "_dnsauth.www");
+        assert!(
+            apply_status("cert-1", &json!({ "Status": 1 }))
+                .unwrap()
+                .can_download
+        );
+    }
+
+    #[test]
+    fn test_revoke_status() {
+        assert_eq!(
+            revoke_status(&json!({ "RequestId": "r" })),
+            RevokeStatus::Revoked
+        );
+        let response = json!({
+            "RevokeDomainValidateAuths": [{
+                "DomainValidateAuthDomain": "example.com",
+                "DomainValidateAuthKey": "_dnsauth",
+                "DomainValidateAuthValue": "value"
+            }]
+        });
+        assert_eq!(
+            revoke_status(&response),
+            RevokeStatus::PendingValidation(vec![
+                "domain example.com TXT _dnsauth value".to_string()
+            ])
+        );
+    }
 }
@@ -0,0 +1,183 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct DomainState {
+    /// 已签发但尚未部署到全部目标的证书 id
+    pub certificate_id: Option<String>,
+    /// 该证书已成功部署的目标
+    #[serde(default)]
+    pub deployed: Vec<String>,
+}
+
+impl DomainState {
+    pub fn is_pending(&self) -> bool {
+        self.certificate_id.is_some()
+    }
+}
+
+/// 本地存储目录，记录每个域名的更新进度
+#[derive(Debug, Clone)]
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Store { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 默认存储目录为域名配置文件同级的 `.csu` 目录
+    pub fn for_domains_file(domains_file: &str) -> Self {
+        let parent = Path::new(domains_file)
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+        Store::new(parent.join(".csu"))
+    }
+
+    fn state_path(&self, domain: &str) -> PathBuf {
+        self.root
+            .join("state")
+            .join(format!("{}.json", file_name(domain)))
+    }
+
+    pub fn state(&self, domain: &str) -> Result<DomainState> {
+        let path = self.state_path(domain);
+        if !path.exists() {
+            return Ok(DomainState::default());
+        }
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save_state(&self, domain: &str, state: &DomainState) -> Result<()> {
+        let path = self.state_path(domain);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(state)?)?;
+        Ok(())
+    }
+
+    fn certificate_dir(&self, domain: &str) -> PathBuf {
+        self.root.join("certs").join(file_name(domain))
+    }
+
+    /// 保存证书链（不含私钥），全部目标部署成功后再通过 `set_current_certificate` 记录为当前证书
+    pub fn save_certificate(
+        &self,
+        domain: &str,
+        certificate_id: &str,
+        full_chain: &str,
+    ) -> Result<()> {
+        let dir = self.certificate_dir(domain);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("{}.pem", certificate_id)), full_chain)?;
+        Ok(())
+    }
+
+    /// 记录域名当前使用的证书
+    pub fn set_current_certificate(&self, domain: &str, certificate_id: &str) -> Result<()> {
+        let dir = self.certificate_dir(domain);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("current"), certificate_id)?;
+        Ok(())
+    }
+
+    pub fn current_certificate_id(&self, domain: &str) -> Result<Option<String>> {
+        let path = self.certificate_dir(domain).join("current");
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?.trim().to_string()))
+    }
+
+    /// 记录已吊销的证书 id，避免再次被复用
+    pub fn add_revoked(&self, certificate_id: &str) -> Result<()> {
+        fs::create_dir_all(&self.root)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.root.join("revoked"))?;
+        writeln!(file, "{}", certificate_id)?;
+        Ok(())
+    }
+
+    pub fn is_revoked(&self, certificate_id: &str) -> Result<bool> {
+        let path = self.root.join("revoked");
+        if !path.exists() {
+            return Ok(false);
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .any(|line| line.trim() == certificate_id))
+    }
+
+    pub fn clear_state(&self, domain: &str) -> Result<()> {
+        let path = self.state_path(domain);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn file_name(domain: &str) -> String {
+    domain.replace('*', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> Store {
+        let root = std::env::temp_dir().join(format!("csu-store-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        Store::new(root)
+    }
+
+    /// 中断后重新运行时读取到未完成的证书和已部署的目标，全部完成后清除
+    #[test]
+    fn test_resume_state() {
+        let store = temp_store("resume");
+        assert!(!store.state("*.example.com").unwrap().is_pending());
+
+        let state = DomainState {
+            certificate_id: Some("cert-1".to_string()),
+            deployed: vec!["cdn:tencent".to_string()],
+        };
+        store.save_state("*.example.com", &state).unwrap();
+        let resumed = store.state("*.example.com").unwrap();
+        assert!(resumed.is_pending());
+        assert_eq!(resumed.certificate_id.as_deref(), Some("cert-1"));
+        assert_eq!(resumed.deployed, vec!["cdn:tencent".to_string()]);
+        assert!(!store.state("example.com").unwrap().is_pending());
+
+        // 部署完成前不更新当前证书
+        store
+            .save_certificate("*.example.com", "cert-1", "CHAIN")
+            .unwrap();
+        assert_eq!(store.current_certificate_id("*.example.com").unwrap(), None);
+        store
+            .set_current_certificate("*.example.com", "cert-1")
+            .unwrap();
+        assert_eq!(
+            store
+                .current_certificate_id("*.example.com")
+                .unwrap()
+                .as_deref(),
+            Some("cert-1")
+        );
+
+        store.clear_state("*.example.com").unwrap();
+        assert!(!store.state("*.example.com").unwrap().is_pending());
+        fs::remove_dir_all(store.root()).unwrap();
+    }
+}
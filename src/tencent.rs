@@ -0,0 +1,138 @@
+use crate::Result;
+use crate::digest::{hmac_sha256, sha256_hex, to_hex};
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde_json::Value;
+use tracing::debug;
+
+const ALGORITHM: &str = "TC3-HMAC-SHA256";
+const CONTENT_TYPE: &str = "application/json; charset=utf-8";
+const DEFAULT_REGION: &str = "ap-guangzhou";
+
+/// 调用腾讯云 API 3.0（签名方法 TC3-HMAC-SHA256）。
+/// 固定版本的 tencent-sdk 只包含申请、下载、上传证书和 TXT 记录的接口，其他接口通过该客户端调用
+pub struct TencentClient {
+    client: Client,
+    secret_id: String,
+    secret_key: String,
+}
+
+/// 一次接口调用：服务名决定接口域名 `<service>.tencentcloudapi.com`
+pub struct Action<'a> {
+    pub service: &'a str,
+    pub version: &'a str,
+    pub action: &'a str,
+}
+
+impl TencentClient {
+    pub fn new(secret_id: &str, secret_key: &str) -> Self {
+        TencentClient {
+            client: Client::new(),
+            secret_id: secret_id.to_string(),
+            secret_key: secret_key.to_string(),
+        }
+    }
+
+    /// 发送请求，返回 `Response` 对象；接口返回 `Response.Error` 时转换为错误
+    pub async fn request(&self, action: &Action<'_>, payload: &Value) -> Result<Value> {
+        let host = format!("{}.tencentcloudapi.com", action.service);
+        let body = payload.to_string();
+        let now = Utc::now();
+        let authorization = self.authorization(action.service, &host, body.as_bytes(), now)?;
+
+        debug!("Tencent Cloud request: {} {}", host, action.action);
+        let response = self
+            .client
+            .post(format!("https://{}", host))
+            .header("content-type", CONTENT_TYPE)
+            .header("host", &host)
+            .header("x-tc-action", action.action)
+            .header("x-tc-version", action.version)
+            .header("x-tc-timestamp", now.timestamp().to_string())
+            .header("x-tc-region", DEFAULT_REGION)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        let mut value: Value = serde_json::from_str(&body).map_err(|_| {
+            AppError::CloudError(format!(
+                "tencent cloud {} failed: HTTP {} {}",
+                action.action, status, body
+            ))
+        })?;
+        let response = value["Response"].take();
+        if let Some(error) = response.get("Error") {
+            return Err(AppError::CloudError(format!(
+                "tencent cloud {} failed: {} {}",
+                action.action,
+                error["Code"].as_str().unwrap_or_default(),
+                error["Message"].as_str().unwrap_or_default()
+            )));
+        }
+        Ok(response)
+    }
+
+    /// 计算 `authorization` 请求头，参与签名的请求头为 content-type 和 host
+    fn authorization(
+        &self,
+        service: &str,
+        host: &str,
+        payload: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<String> {
+        let date = now.format("%Y-%m-%d").to_string();
+        let canonical_request = format!(
+            "POST\n/\n\ncontent-type:{}\nhost:{}\n\ncontent-type;host\n{}",
+            CONTENT_TYPE,
+            host,
+            sha256_hex(payload)?
+        );
+        let credential_scope = format!("{}/{}/tc3_request", date, service);
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM,
+            now.timestamp(),
+            credential_scope,
+            sha256_hex(canonical_request.as_bytes())?
+        );
+        let secret_date = hmac_sha256(
+            format!("TC3{}", self.secret_key).as_bytes(),
+            date.as_bytes(),
+        )?;
+        let secret_service = hmac_sha256(&secret_date, service.as_bytes())?;
+        let secret_signing = hmac_sha256(&secret_service, b"tc3_request")?;
+        let signature = to_hex(&hmac_sha256(&secret_signing, string_to_sign.as_bytes())?);
+        Ok(format!(
+            "{} Credential={}/{}, SignedHeaders=content-type;host, Signature={}",
+            ALGORITHM, self.secret_id, credential_scope, signature
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorization() {
+        let client = TencentClient::new("AKIDEXAMPLE", "SECRETEXAMPLE");
+        let now = DateTime::from_timestamp(1700000000, 0).unwrap();
+        let authorization = client
+            .authorization(
+                "ssl",
+                "ssl.tencentcloudapi.com",
+                br#"{"CertificateId":"abc"}"#,
+                now,
+            )
+            .unwrap();
+        assert_eq!(
+            authorization,
+            "TC3-HMAC-SHA256 Credential=AKIDEXAMPLE/2023-11-14/ssl/tc3_request, \
+             SignedHeaders=content-type;host, \
+             Signature=08ab3394e2e743f27d5826a0642c8df70fe6df7039eb8e0d0b347b977a721f83"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use super::Publisher;
+use crate::Result;
+use crate::digest::sha256_hex;
+use crate::error::AppError;
+use crate::sigv4::{Credentials, SigV4};
+use crate::ssl::FileChallenge;
+use chrono::Utc;
+use reqwest::{Client, Method, Url};
+use serde::Deserialize;
+use tracing::info;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BucketTarget {
+    /// 存储桶访问地址，如 `https://bucket.cos.ap-guangzhou.myqcloud.com`
+    pub endpoint: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// 对象键前缀，验证文件的对象键为前缀加验证路径
+    #[serde(default)]
+    pub prefix: String,
+}
+
+pub struct BucketPublisher {
+    pub target: BucketTarget,
+    client: Client,
+    signer: SigV4,
+}
+
+impl BucketPublisher {
+    pub fn new(target: BucketTarget) -> Self {
+        let signer = SigV4::new(
+            Credentials {
+                access_key_id: target.access_key_id.clone(),
+                secret_access_key: target.secret_access_key.clone(),
+            },
+            &target.region,
+            "s3",
+        );
+        BucketPublisher {
+            target,
+            client: Client::new(),
+            signer,
+        }
+    }
+
+    fn object_url(&self, challenge: &FileChallenge) -> Result<Url> {
+        let key = format!("{}{}", self.target.prefix.trim_matches('/'), challenge.path);
+        let url = format!(
+            "{}/{}",
+            self.target.endpoint.trim_end_matches('/'),
+            key.trim_start_matches('/')
+        );
+        Url::parse(&url).map_err(|e| AppError::ConfigError(format!("{}: {}", url, e)))
+    }
+
+    async fn send(&self, method: Method, url: Url, body: &str) -> Result<()> {
+        let payload_hash = sha256_hex(body.as_bytes())?;
+        let headers = self.signer.sign(
+            method.as_str(),
+            &url,
+            &[("x-amz-content-sha256", &payload_hash)],
+            body.as_bytes(),
+            Utc::now(),
+        )?;
+        let mut request = self
+            .client
+            .request(method.clone(), url.clone())
+            .header("x-amz-content-sha256", &payload_hash)
+            .body(body.to_string());
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(AppError::CloudError(format!(
+                "{} {} failed: HTTP {} {}",
+                method,
+                url,
+                response.status(),
+                response.text().await.unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Publisher for BucketPublisher {
+    async fn publish(&self, challenge: &FileChallenge) -> Result<()> {
+        let url = self.object_url(challenge)?;
+        self.send(Method::PUT, url.clone(), &challenge.content)
+            .await?;
+        info!(
+            "Uploaded validation file for domain {}: {}",
+            challenge.domain, url
+        );
+        Ok(())
+    }
+
+    async fn cleanup(&self, challenge: &FileChallenge) -> Result<()> {
+        let url = self.object_url(challenge)?;
+        self.send(Method::DELETE, url, "").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// 读取一个 HTTP 请求，返回请求头和请求体
+    async fn read_request(stream: &mut TcpStream) -> (String, String) {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            buffer.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buffer).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length || n == 0 {
+                    return (head.to_string(), body.to_string());
+                }
+            }
+        }
+    }
+
+    /// 本地模拟存储桶，确认上传和删除的对象键以及签名请求头
+    #[tokio::test]
+    async fn test_publish_and_cleanup() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in ["200 OK", "204 No Content", "403 Forbidden"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                requests.push(read_request(&mut stream).await);
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let publisher = BucketPublisher::new(BucketTarget {
+            endpoint: format!("http://127.0.0.1:{}/", port),
+            region: "ap-guangzhou".to_string(),
+            access_key_id: "AKID".to_string(),
+            secret_access_key: "SECRET".to_string(),
+            prefix: "/site/".to_string(),
+        });
+        let challenge = FileChallenge {
+            domain: "example.com".to_string(),
+            path: "/.well-known/pki-validation/fileauth.txt".to_string(),
+            content: "token".to_string(),
+        };
+        publisher.publish(&challenge).await.unwrap();
+        publisher.cleanup(&challenge).await.unwrap();
+        assert!(publisher.cleanup(&challenge).await.is_err());
+
+        let requests = server.await.unwrap();
+        let (head, body) = &requests[0];
+        assert!(head.starts_with("PUT /site/.well-known/pki-validation/fileauth.txt HTTP/1.1"));
+        assert!(head.contains("AWS4-HMAC-SHA256 Credential=AKID/"));
+        assert_eq!(body, "token");
+        let (head, _) = &requests[1];
+        assert!(head.starts_with("DELETE /site/.well-known/pki-validation/fileauth.txt HTTP/1.1"));
+    }
+}
@@ -0,0 +1,194 @@
+use super::Publisher;
+use crate::Result;
+use crate::ssl::FileChallenge;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct HttpResponderOptions {
+    /// 监听地址，域名的 80 端口需要转发到这里
+    #[serde(default = "default_listen")]
+    pub listen: String,
+}
+
+fn default_listen() -> String {
+    "0.0.0.0:80".to_string()
+}
+
+/// 按 `(域名, 路径)` 保存的验证内容
+type Challenges = Arc<Mutex<HashMap<(String, String), String>>>;
+
+/// 首次发布时启动 HTTP 服务，全部验证文件清理后停止
+pub struct HttpPublisher {
+    pub options: HttpResponderOptions,
+    challenges: Challenges,
+    server: tokio::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl HttpPublisher {
+    pub fn new(options: HttpResponderOptions) -> Self {
+        HttpPublisher {
+            options,
+            challenges: Arc::new(Mutex::new(HashMap::new())),
+            server: tokio::sync::Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Publisher for HttpPublisher {
+    async fn publish(&self, challenge: &FileChallenge) -> Result<()> {
+        self.challenges.lock().unwrap().insert(
+            (challenge.domain.to_lowercase(), challenge.path.clone()),
+            challenge.content.clone(),
+        );
+        let mut server = self.server.lock().await;
+        if server.is_none() {
+            let listener = TcpListener::bind(&self.options.listen).await?;
+            info!("Serving validation files on {}", self.options.listen);
+            let challenges = self.challenges.clone();
+            *server = Some(tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(respond(stream, challenges.clone()));
+                }
+            }));
+        }
+        Ok(())
+    }
+
+    async fn cleanup(&self, challenge: &FileChallenge) -> Result<()> {
+        let is_empty = {
+            let mut challenges = self.challenges.lock().unwrap();
+            challenges.remove(&(challenge.domain.to_lowercase(), challenge.path.clone()));
+            challenges.is_empty()
+        };
+        if is_empty && let Some(server) = self.server.lock().await.take() {
+            server.abort();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for HttpPublisher {
+    fn drop(&mut self) {
+        if let Some(server) = self.server.get_mut().take() {
+            server.abort();
+        }
+    }
+}
+
+/// 只处理请求行和 Host 请求头，路径匹配时返回验证内容，否则返回 404
+async fn respond(mut stream: TcpStream, challenges: Challenges) {
+    let mut buf = vec![0u8; 8192];
+    let mut len = 0;
+    while len < buf.len() {
+        match stream.read(&mut buf[len..]).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => len += n,
+        }
+        if buf[..len].windows(4).any(|window| window == b"\r\n\r\n") {
+            break;
+        }
+    }
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let mut lines = request.lines();
+    let path = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or_default()
+        .to_string();
+    let host = lines
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("host")
+                .then(|| value.trim().to_string())
+        })
+        .map(|host| host.split(':').next().unwrap_or_default().to_lowercase())
+        .unwrap_or_default();
+    debug!("Validation request: {} {}", host, path);
+
+    let content = {
+        let challenges = challenges.lock().unwrap();
+        challenges.get(&(host, path.clone())).cloned().or_else(|| {
+            challenges
+                .iter()
+                .find(|((_, challenge_path), _)| *challenge_path == path)
+                .map(|(_, content)| content.clone())
+        })
+    };
+    let response = match content {
+        Some(content) => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content.len(),
+            content
+        ),
+        None => {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        }
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(port: u16, host: &str, path: &str) -> std::io::Result<String> {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await?;
+        let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, host);
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    /// 发布后按域名和路径返回验证内容，全部清理后停止服务
+    #[tokio::test]
+    async fn test_publish_serves_and_cleanup_stops() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let publisher = HttpPublisher::new(HttpResponderOptions {
+            listen: format!("127.0.0.1:{}", port),
+        });
+        let challenge = |domain: &str, content: &str| FileChallenge {
+            domain: domain.to_string(),
+            path: "/.well-known/pki-validation/fileauth.txt".to_string(),
+            content: content.to_string(),
+        };
+        let first = challenge("a.example.com", "token-a");
+        let second = challenge("b.example.com", "token-b");
+        publisher.publish(&first).await.unwrap();
+        publisher.publish(&second).await.unwrap();
+
+        let response = get(port, "B.example.com:80", &second.path).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("\r\n\r\ntoken-b"));
+        let response = get(port, "a.example.com", "/other.txt").await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+
+        publisher.cleanup(&first).await.unwrap();
+        let response = get(port, "b.example.com", &second.path).await.unwrap();
+        assert!(response.ends_with("token-b"));
+        publisher.cleanup(&second).await.unwrap();
+        // 中止的任务在下一次调度时才释放监听端口
+        let mut stopped = false;
+        for _ in 0..10 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            if get(port, "b.example.com", &second.path).await.is_err() {
+                stopped = true;
+                break;
+            }
+        }
+        assert!(stopped);
+    }
+}
@@ -0,0 +1,31 @@
+mod bucket;
+mod http;
+mod webroot;
+
+pub use bucket::{BucketPublisher, BucketTarget};
+pub use http::{HttpPublisher, HttpResponderOptions};
+pub use webroot::{WebrootPublisher, WebrootTarget};
+
+use crate::Result;
+use crate::ssl::FileChallenge;
+use serde::Deserialize;
+
+/// 文件验证（FILE）方式下验证文件的发布方式
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FileValidation {
+    /// 写入本机 Web 服务器的站点根目录
+    Webroot(WebrootTarget),
+    /// 上传到 S3 兼容的对象存储桶
+    Bucket(BucketTarget),
+    /// 由内置的 HTTP 服务直接响应验证请求
+    Http(HttpResponderOptions),
+}
+
+#[async_trait::async_trait]
+pub trait Publisher: Send + Sync {
+    /// 发布验证文件，使 `http://<domain><path>` 返回验证内容
+    async fn publish(&self, challenge: &FileChallenge) -> Result<()>;
+    /// 证书签发后删除验证文件
+    async fn cleanup(&self, challenge: &FileChallenge) -> Result<()>;
+}
@@ -0,0 +1,115 @@
+use super::Publisher;
+use crate::Result;
+use crate::error::AppError;
+use crate::ssl::FileChallenge;
+use serde::Deserialize;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use tracing::info;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebrootTarget {
+    /// 站点根目录，验证文件写入 `<path>/.well-known/pki-validation/` 等子目录
+    pub path: String,
+}
+
+pub struct WebrootPublisher {
+    pub target: WebrootTarget,
+}
+
+impl WebrootPublisher {
+    pub fn new(target: WebrootTarget) -> Self {
+        WebrootPublisher { target }
+    }
+
+    /// 验证路径由服务商返回，只允许普通路径组成部分，避免写到站点根目录之外
+    fn file_path(&self, challenge: &FileChallenge) -> Result<PathBuf> {
+        let relative = Path::new(challenge.path.trim_start_matches('/'));
+        let mut components = relative.components().peekable();
+        if components.peek().is_none()
+            || !components.all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(AppError::CloudError(format!(
+                "invalid validation file path for domain {}: {}",
+                challenge.domain, challenge.path
+            )));
+        }
+        Ok(PathBuf::from(&self.target.path).join(relative))
+    }
+}
+
+#[async_trait::async_trait]
+impl Publisher for WebrootPublisher {
+    async fn publish(&self, challenge: &FileChallenge) -> Result<()> {
+        let path = self.file_path(challenge)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, &challenge.content).await?;
+        info!(
+            "Wrote validation file for domain {}: {}",
+            challenge.domain,
+            path.display()
+        );
+        Ok(())
+    }
+
+    async fn cleanup(&self, challenge: &FileChallenge) -> Result<()> {
+        match tokio::fs::remove_file(self.file_path(challenge)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(path: &str) -> FileChallenge {
+        FileChallenge {
+            domain: "example.com".to_string(),
+            path: path.to_string(),
+            content: "token".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_cleanup() {
+        let root = std::env::temp_dir().join(format!("csu-webroot-{}", std::process::id()));
+        let publisher = WebrootPublisher::new(WebrootTarget {
+            path: root.to_string_lossy().to_string(),
+        });
+        let challenge = challenge("/.well-known/pki-validation/fileauth.txt");
+        publisher.publish(&challenge).await.unwrap();
+        let file = root.join(".well-known/pki-validation/fileauth.txt");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "token");
+        publisher.cleanup(&challenge).await.unwrap();
+        assert!(!file.exists());
+        // 文件已删除时清理不报错
+        publisher.cleanup(&challenge).await.unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_file_path_rejects_traversal() {
+        let publisher = WebrootPublisher::new(WebrootTarget {
+            path: "/var/www".to_string(),
+        });
+        assert_eq!(
+            publisher
+                .file_path(&challenge("/.well-known/pki-validation/a.txt"))
+                .unwrap(),
+            PathBuf::from("/var/www/.well-known/pki-validation/a.txt")
+        );
+        for path in [
+            "/../etc/passwd",
+            "/.well-known/../../a.txt",
+            "/./a.txt",
+            "/",
+            "",
+        ] {
+            assert!(publisher.file_path(&challenge(path)).is_err(), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::Result;
+use crate::domain::{CloudProvider, Domain};
+use crate::error::AppError;
+use crate::store::Store;
+use hickory_resolver::Resolver;
+use hickory_resolver::proto::rr::{RData, RecordType};
+use publicsuffix::{IcannList, Psl};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+const PUBLIC_SUFFIX_LIST_URL: &str = "https://publicsuffix.org/list/public_suffix_list.dat";
+/// 公共后缀列表缓存超过 7 天后重新下载
+const PUBLIC_SUFFIX_LIST_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// 在托管的根域名中查找域名所属的最长后缀
+pub fn find_zone<'a>(name: &str, zones: &'a [String]) -> Option<&'a String> {
+    let name = name.trim_start_matches("*.").to_lowercase();
+    zones
+        .iter()
+        .filter(|zone| {
+            let zone = zone.to_lowercase();
+            name == zone || name.ends_with(&format!(".{}", zone))
+        })
+        .max_by_key(|zone| zone.len())
+}
+
+/// 查询域名的 CNAME 记录，返回不带结尾点的目标域名
+pub async fn resolve_cname(name: &str) -> Result<String> {
+    let resolver = Resolver::builder_tokio()
+        .map_err(|e| AppError::Other(format!("failed to create dns resolver: {}", e)))?
+        .build();
+    let lookup = resolver
+        .lookup(name, RecordType::CNAME)
+        .await
+        .map_err(|e| AppError::Other(format!("failed to lookup CNAME of {}: {}", name, e)))?;
+    lookup
+        .iter()
+        .find_map(|rdata| match rdata {
+            RData::CNAME(cname) => Some(cname.0.to_ascii().trim_end_matches('.').to_lowercase()),
+            _ => None,
+        })
+        .ok_or_else(|| AppError::Other(format!("no CNAME record found for {}", name)))
+}
+
+/// 按公共后缀列表取可注册域名，如 `a.b.example.co.uk` 返回 `example.co.uk`
+pub fn public_suffix_zone(name: &str, list: &IcannList) -> Option<String> {
+    let name = name.trim_start_matches("*.").to_lowercase();
+    let domain = list.domain(name.as_bytes())?;
+    std::str::from_utf8(domain.as_bytes())
+        .ok()
+        .map(str::to_string)
+}
+
+/// 为未配置 `original_name` 的域名确定 DNS 根域名：
+/// 优先在 dns_provider 托管的域名中取最长后缀，找不到时使用公共后缀列表
+pub async fn resolve_zones(domains: &mut [Domain], store: &Store) -> Result<()> {
+    let mut zones: Vec<(CloudProvider, Vec<String>)> = Vec::new();
+    let mut public_suffix_list: Option<IcannList> = None;
+
+    // 文件验证不添加 DNS 记录，不需要根域名
+    for domain in domains
+        .iter_mut()
+        .filter(|domain| domain.original_name.is_empty() && domain.file_validation.is_none())
+    {
+        let Some(dns_provider) = domain.dns_provider.clone() else {
+            continue;
+        };
+        if !zones.iter().any(|(provider, _)| provider == &dns_provider) {
+            let account_zones = match crate::dns_client(&dns_provider)?.list_zones().await {
+                Ok(account_zones) => account_zones,
+                Err(e) => {
+                    warn!(
+                        "Failed to list DNS zones for account {}: {}",
+                        dns_provider.account(),
+                        e
+                    );
+                    Vec::new()
+                }
+            };
+            zones.push((dns_provider.clone(), account_zones));
+        }
+        let account_zones = zones
+            .iter()
+            .find(|(provider, _)| provider == &dns_provider)
+            .map(|(_, account_zones)| account_zones.as_slice())
+            .unwrap_or_default();
+
+        let zone = match find_zone(&domain.name, account_zones) {
+            Some(zone) => Some(zone.clone()),
+            None => {
+                if public_suffix_list.is_none() {
+                    public_suffix_list = Some(load_public_suffix_list(store).await?);
+                }
+                public_suffix_list
+                    .as_ref()
+                    .and_then(|list| public_suffix_zone(&domain.name, list))
+            }
+        };
+        let zone = zone.ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "cannot determine DNS zone for domain {}, please set original_name",
+                domain.name
+            ))
+        })?;
+        info!("Resolved DNS zone for domain {}: {}", domain.name, zone);
+        domain.original_name = zone;
+    }
+    Ok(())
+}
+
+/// 公共后缀列表缓存在存储目录中，不存在或已过期时下载；下载失败时继续使用过期的缓存
+async fn load_public_suffix_list(store: &Store) -> Result<IcannList> {
+    let path = store.root().join("public_suffix_list.dat");
+    let content = if cache_fresh(&path, SystemTime::now()) {
+        fs::read_to_string(&path)?
+    } else {
+        match download_public_suffix_list().await {
+            Ok(content) => {
+                fs::create_dir_all(store.root())?;
+                let temp_path = path.with_extension("dat.tmp");
+                fs::write(&temp_path, &content)?;
+                fs::rename(&temp_path, &path)?;
+                content
+            }
+            Err(e) if path.exists() => {
+                warn!(
+                    "Failed to update public suffix list, using cached copy: {}",
+                    e
+                );
+                fs::read_to_string(&path)?
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    content
+        .parse()
+        .map_err(|e| AppError::Other(format!("invalid public suffix list: {:?}", e)))
+}
+
+async fn download_public_suffix_list() -> Result<String> {
+    info!("Downloading public suffix list: {}", PUBLIC_SUFFIX_LIST_URL);
+    Ok(reqwest::get(PUBLIC_SUFFIX_LIST_URL)
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// 缓存文件存在且修改时间未超过 `PUBLIC_SUFFIX_LIST_MAX_AGE`
+fn cache_fresh(path: &Path, now: SystemTime) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| now.duration_since(modified).ok())
+        .is_some_and(|age| age < PUBLIC_SUFFIX_LIST_MAX_AGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_longest_suffix_and_public_suffix_fallback() {
+        let zones = vec!["example.co.uk".to_string(), "b.example.co.uk".to_string()];
+        assert_eq!(
+            find_zone("a.b.example.co.uk", &zones).map(String::as_str),
+            Some("b.example.co.uk")
+        );
+        assert_eq!(find_zone("example.com", &zones), None);
+
+        let list: IcannList =
+            "// ===BEGIN ICANN DOMAINS===\nuk\nco.uk\ncom\n// ===END ICANN DOMAINS==="
+                .parse()
+                .unwrap();
+        assert_eq!(
+            public_suffix_zone("a.b.example.co.uk", &list).as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(
+            public_suffix_zone("*.cdn.example.com", &list).as_deref(),
+            Some("example.com")
+        );
+    }
+
+    #[test]
+    fn test_public_suffix_list_cache_max_age() {
+        let path = std::env::temp_dir().join(format!("csu-psl-{}.dat", std::process::id()));
+        assert!(!cache_fresh(&path, SystemTime::now()));
+        fs::write(&path, "com").unwrap();
+        assert!(cache_fresh(&path, SystemTime::now()));
+        assert!(!cache_fresh(
+            &path,
+            SystemTime::now() + PUBLIC_SUFFIX_LIST_MAX_AGE
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}